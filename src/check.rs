@@ -0,0 +1,1185 @@
+use eyre::Result;
+use std::{collections::HashMap, fmt, io::Write, path::PathBuf};
+
+use crate::{
+    config::{self, ColorSpec, Config, RangeSpec, TimeRange, TimeTrack},
+    format::Format,
+    keys::{Action, KeyChord, KeyMap},
+    query, to_minutes, MINUTES_PER_DAY,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+// Config issue found by `problems`, `path` points to the value within the file,
+// e.g. 'tracks[1].ranges[0]'
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}\n  at {} {}", self.severity, self.message, self.file.display(), self.path)
+    }
+}
+
+#[inline]
+fn fmt_time((hour, minute): (u16, u16)) -> String {
+    format!("{:02}:{:02}", hour, minute)
+}
+
+#[inline]
+fn title(track: &TimeTrack) -> String {
+    match (track.name.is_empty(), track.shortname.is_empty()) {
+        (false, _) => format!("{:?}", track.name),
+        (true, false) => format!("{:?}", track.shortname),
+        (true, true) => format!("#{}", track.origin.index),
+    }
+}
+
+// Every problem of the config in one pass, errors make the config unusable,
+// warnings point to the things that are valid but probably not intended.
+pub fn problems(config: &Config) -> Vec<Problem> {
+    let mut problems = vec![];
+    if config.tracks.is_empty() {
+        problems.push(Problem {
+            severity: Severity::Error,
+            file: config.files.first().cloned().unwrap_or_default(),
+            path: "tracks".to_string(),
+            message: "No tracks defined in the config, which implies it's an empty file. \
+Define some tracks, specify another config file, or check the github repo for pre-existing configurations"
+                .to_string(),
+        });
+    }
+    if !config.theme.is_empty() {
+        let name = &config.theme;
+        if config.theme(name).is_none() {
+            problems.push(Problem {
+                severity: Severity::Error,
+                file: config.files.first().cloned().unwrap_or_default(),
+                path: "theme".to_string(),
+                message: format!(
+                    "unknown theme {:?}, known themes are {:?}",
+                    name,
+                    config.theme_names()
+                ),
+            });
+        }
+    }
+    check_palette(config, &mut problems);
+    check_keys(config, &mut problems);
+    check_overlap(config, &mut problems);
+    for track in &config.tracks {
+        check_track(track, config, &mut problems);
+    }
+    check_duplicates(config, &mut problems);
+    problems
+}
+
+#[inline]
+pub(crate) fn unknown_color(name: &str, config: &Config) -> String {
+    let known: Vec<&String> = config.palette.keys().collect();
+    format!("unknown color {:?}, palette colors are {:?}", name, known)
+}
+
+fn check_overlap(config: &Config, problems: &mut Vec<Problem>) {
+    let Some(overlap) = &config.overlap else {
+        return;
+    };
+    let file = config.files.first().cloned().unwrap_or_default();
+    let mut report = |path: String, message: String| {
+        problems.push(Problem { severity: Severity::Error, file: file.clone(), path, message })
+    };
+    for (i, name) in overlap.tracks.iter().enumerate() {
+        if let Err(e) = query::find_track(name, &config.tracks) {
+            report(format!("overlap.tracks[{}]", i), e);
+        }
+    }
+    if let Some(ColorSpec::Palette(name)) = &overlap.range {
+        if config.color(&ColorSpec::Palette(name.clone())).is_none() {
+            report("overlap.range".to_string(), unknown_color(name, config));
+        }
+    }
+}
+
+// keys that don't parse, and keys bound to more than one action, defaults included
+fn check_keys(config: &Config, problems: &mut Vec<Problem>) {
+    let file = config.files.first().cloned().unwrap_or_default();
+    let map = KeyMap::new(&config.keys);
+    if map.keys(Action::Quit).is_empty() {
+        problems.push(Problem {
+            severity: Severity::Warning,
+            file: file.clone(),
+            path: "keys.quit".to_string(),
+            message: "no key quits the app".to_string(),
+        });
+    }
+    for (action, keys) in &config.keys {
+        let path = format!("keys.{}", serde_variant(action));
+        for key in keys {
+            let message = match KeyChord::parse(key) {
+                Err(e) => e,
+                Ok(chord) => {
+                    // a conflict of two config actions is reported at the first one
+                    let others: Vec<String> = map
+                        .others(*action, chord)
+                        .into_iter()
+                        .filter(|other| other > action || !config.keys.contains_key(other))
+                        .map(|other| serde_variant(&other))
+                        .collect();
+                    if others.is_empty() {
+                        continue;
+                    }
+                    format!("key {:?} is bound to {:?} as well", key, others)
+                }
+            };
+            problems.push(Problem {
+                severity: Severity::Error,
+                file: file.clone(),
+                path: path.clone(),
+                message,
+            });
+        }
+    }
+}
+
+// action name as written in the config
+#[inline]
+fn serde_variant<T: serde::Serialize>(value: &T) -> String {
+    ron::to_string(value).unwrap_or_default()
+}
+
+fn check_palette(config: &Config, problems: &mut Vec<Problem>) {
+    let file = config.files.first().cloned().unwrap_or_default();
+    let mut report = |severity, path: String, message: String| {
+        problems.push(Problem { severity, file: file.clone(), path, message })
+    };
+    for (name, color) in &config.palette {
+        if let ColorSpec::Palette(other) = color {
+            report(
+                Severity::Error,
+                format!("palette.{}", name),
+                format!(
+                    "palette color {:?} refers to {:?}, palette colors can't refer to each other",
+                    name, other
+                ),
+            );
+        }
+        if config::is_color_name(name) {
+            report(
+                Severity::Warning,
+                format!("palette.{}", name),
+                format!(
+                    "palette color {:?} is never used, it's shadowed by the color of the same name",
+                    name
+                ),
+            );
+        }
+    }
+    let colors = &config.colors;
+    for (field, spec) in [
+        ("base", &colors.base),
+        ("fill_fg", &colors.fill_fg),
+        ("clock", &colors.clock),
+        ("title", &colors.title),
+    ] {
+        if let Some(ColorSpec::Palette(name)) = spec {
+            if config.color(&ColorSpec::Palette(name.clone())).is_none() {
+                report(Severity::Error, format!("colors.{}", field), unknown_color(name, config));
+            }
+        }
+    }
+}
+
+// calendar files are read by `config::load_config` only, so they are checked here and not in `problems`
+fn check_calendars(config: &Config, problems: &mut Vec<Problem>) {
+    for track in &config.tracks {
+        let Some(calendar) = &track.calendar else {
+            continue;
+        };
+        if let Err(e) = config::read_calendar(track, calendar) {
+            let causes: Vec<String> = e.chain().map(|e| e.to_string().replace('\n', " ")).collect();
+            problems.push(Problem {
+                severity: Severity::Error,
+                file: track.origin.file.clone(),
+                path: format!("tracks[{}].calendar.file", track.origin.index),
+                message: format!("track {}: {}", title(track), causes.join(": ")),
+            });
+        }
+    }
+}
+
+fn check_track(track: &TimeTrack, config: &Config, problems: &mut Vec<Problem>) {
+    let mut report = |severity, field: String, message: String| {
+        problems.push(Problem {
+            severity,
+            file: track.origin.file.clone(),
+            path: format!("tracks[{}]{}", track.origin.index, field),
+            message: format!("track {}: {}", title(track), message),
+        })
+    };
+
+    if track.name.is_empty() && track.shortname.is_empty() {
+        report(
+            Severity::Error,
+            String::new(),
+            "unspecified title, specify at least one of the fields 'name', 'shortname'".to_string(),
+        );
+    }
+    for (field, value) in [(".name", &track.name), (".shortname", &track.shortname)] {
+        if value.len() > u16::MAX.into() {
+            report(
+                Severity::Error,
+                field.to_string(),
+                format!(
+                    "name is way too long, rename it to fit at most {} characters, \
+although the real limit is `terminal line length` - 13",
+                    u16::MAX
+                ),
+            );
+        }
+    }
+    if track.offset.0.abs() > 23 || track.offset.1.abs() > 59 {
+        report(
+            Severity::Error,
+            ".offset".to_string(),
+            format!(
+                "UTC offset {} is out of range, valid values are -23..23 for hour and -59..59 for minute",
+                config::fmt_offset(track.offset)
+            ),
+        );
+    }
+    if let Some((lat, lon)) = track.location {
+        if !(-90.0..=90.).contains(&lat) || !(-180.0..=180.).contains(&lon) {
+            report(
+                Severity::Error,
+                ".location".to_string(),
+                format!(
+                    "location ({}, {}) is out of range, valid values are -90..90 for latitude \
+and -180..180 for longitude",
+                    lat, lon
+                ),
+            );
+        }
+    }
+
+    if let Some(calendar) = &track.calendar {
+        let colors = std::iter::once((".calendar.color".to_string(), &calendar.color)).chain(
+            calendar
+                .categories
+                .iter()
+                .map(|(c, spec)| (format!(".calendar.categories.{}", c), spec)),
+        );
+        for (field, spec) in colors {
+            if let ColorSpec::Palette(name) = spec {
+                if config.color(spec).is_none() {
+                    report(Severity::Error, field, unknown_color(name, config));
+                }
+            }
+        }
+    }
+
+    let valid = check_ranges(&track.ranges, &track.range_spec, "", config, &mut report);
+    let mut dates = HashMap::new();
+    for (i, day) in track.overrides.iter().enumerate() {
+        let field = format!(".overrides[{}]", i);
+        check_ranges(&day.ranges, &day.range_spec, &field, config, &mut report);
+        if let Some(first) = dates.insert(day.date, i) {
+            report(
+                Severity::Warning,
+                format!("{}.date", field),
+                format!("date {} is overridden by overrides[{}] as well", day.date, first),
+            );
+        }
+    }
+
+    // no gaps left for the base color
+    let mut covered = 0;
+    for &i in &valid {
+        let range = &track.ranges[i];
+        if to_minutes(range.start) > covered {
+            break;
+        }
+        covered = u16::max(covered, to_minutes(range.end));
+    }
+    if covered == MINUTES_PER_DAY {
+        report(
+            Severity::Warning,
+            ".ranges".to_string(),
+            "ranges cover the whole day, the base color is never shown".to_string(),
+        );
+    }
+}
+
+// Ranges of a track or of a date override at `field`, the valid ones are returned in chronological order.
+fn check_ranges(
+    ranges: &[TimeRange],
+    spec: &RangeSpec,
+    field: &str,
+    config: &Config,
+    report: &mut impl FnMut(Severity, String, String),
+) -> Vec<usize> {
+    // ranges defined by a template are reported at the template reference
+    let range_field = |i: usize| match spec {
+        RangeSpec::List(_) => format!("{}.ranges[{}]", field, i),
+        RangeSpec::Template(_) => format!("{}.ranges", field),
+    };
+    if let RangeSpec::Template(name) = spec {
+        if !config.templates.contains_key(name) {
+            let known: Vec<&String> = config.templates.keys().collect();
+            report(
+                Severity::Error,
+                format!("{}.ranges", field),
+                format!("unknown range template {:?}, defined templates are {:?}", name, known),
+            );
+        }
+    }
+
+    for (i, range) in ranges.iter().enumerate() {
+        if let ColorSpec::Palette(name) = &range.color_spec {
+            if config.color(&range.color_spec).is_none() {
+                let path = match spec {
+                    RangeSpec::List(_) => format!("{}.ranges[{}].color", field, i),
+                    RangeSpec::Template(_) => format!("{}.ranges", field),
+                };
+                report(Severity::Error, path, unknown_color(name, config));
+            }
+        }
+    }
+
+    let mut valid = vec![];
+    for (i, range) in ranges.iter().enumerate() {
+        let (start, end) = (range.start, range.end);
+        let span = format!("{}-{}", fmt_time(start), fmt_time(end));
+        if start.0 > 23 || end.0 > 24 || start.1 > 59 || end.1 > 59 {
+            report(
+                Severity::Error,
+                range_field(i),
+                format!(
+                    "range {} time is out of range, valid values are 0..24 for hour and 0..59 for minute",
+                    span
+                ),
+            );
+        } else if to_minutes(end) > MINUTES_PER_DAY {
+            report(Severity::Error, range_field(i), format!("range {} ends after 24:00", span));
+        } else if to_minutes(start) >= to_minutes(end) {
+            report(
+                Severity::Error,
+                range_field(i),
+                format!(
+                    "range {} should be chronologically ordered such that 'start' < 'end'",
+                    span
+                ),
+            );
+        } else {
+            valid.push(i);
+        }
+    }
+
+    valid.sort_by_key(|&i| to_minutes(ranges[i].start));
+    for pair in valid.windows(2) {
+        let (prev, next) = (&ranges[pair[0]], &ranges[pair[1]]);
+        if to_minutes(next.start) < to_minutes(prev.end) {
+            report(
+                Severity::Error,
+                range_field(pair[1]),
+                format!(
+                    "range {}-{} overlaps with {}-{}, ranges should be chronologically exclusive",
+                    fmt_time(next.start),
+                    fmt_time(next.end),
+                    fmt_time(prev.start),
+                    fmt_time(prev.end)
+                ),
+            );
+        }
+    }
+    valid
+}
+
+fn check_duplicates(config: &Config, problems: &mut Vec<Problem>) {
+    for (field, get) in [
+        ("name", (|t: &TimeTrack| &t.name) as fn(&TimeTrack) -> &String),
+        ("shortname", |t: &TimeTrack| &t.shortname),
+    ] {
+        let mut seen: HashMap<&String, &TimeTrack> = HashMap::new();
+        for track in &config.tracks {
+            let value = get(track);
+            if value.is_empty() {
+                continue;
+            }
+            if let Some(first) = seen.get(value) {
+                problems.push(Problem {
+                    severity: Severity::Warning,
+                    file: track.origin.file.clone(),
+                    path: format!("tracks[{}].{}", track.origin.index, field),
+                    message: format!(
+                        "track {}: {} {:?} is already used by the track at {} tracks[{}]",
+                        title(track),
+                        field,
+                        value,
+                        first.origin.file.display(),
+                        first.origin.index
+                    ),
+                });
+            } else {
+                seen.insert(value, track);
+            }
+        }
+    }
+}
+
+// Checks config and writes every problem found in the 'file:line:col: severity: message' form,
+// returns the number of errors
+pub fn run<W: Write>(path: PathBuf, out: &mut W) -> Result<usize> {
+    let config = match config::read_configs(path.clone()) {
+        Ok(config) => config,
+        // syntax errors stop the parser, nothing else to check
+        Err(e) => {
+            let causes: Vec<String> = e.chain().map(|e| e.to_string()).collect();
+            writeln!(out, "error: {}", causes.join("\n  "))?;
+            writeln!(out, "{}: 1 error", path.display())?;
+            return Ok(1);
+        }
+    };
+
+    let mut problems = problems(&config);
+    check_calendars(&config, &mut problems);
+    let mut sources: HashMap<PathBuf, HashMap<String, (usize, usize)>> = HashMap::new();
+    for problem in &problems {
+        let spans = sources.entry(problem.file.clone()).or_insert_with(|| {
+            match (Format::from_path(&problem.file), std::fs::read_to_string(&problem.file)) {
+                (Some(format), Ok(src)) => locate_in(format, &src),
+                _ => HashMap::new(),
+            }
+        });
+        match span_of(spans, &problem.path) {
+            Some((line, col)) => writeln!(
+                out,
+                "{}:{}:{}: {}: {}",
+                problem.file.display(),
+                line,
+                col,
+                problem.severity,
+                problem.message
+            )?,
+            None => writeln!(
+                out,
+                "{}: {}: {} (at {})",
+                problem.file.display(),
+                problem.severity,
+                problem.message,
+                problem.path
+            )?,
+        }
+    }
+
+    let errors = problems.iter().filter(|p| p.severity == Severity::Error).count();
+    writeln!(out, "{}: {} errors, {} warnings", path.display(), errors, problems.len() - errors)?;
+    Ok(errors)
+}
+
+// position of the value, or of the closest enclosing one
+fn span_of(spans: &HashMap<String, (usize, usize)>, path: &str) -> Option<(usize, usize)> {
+    let mut path = path;
+    loop {
+        if let Some(span) = spans.get(path) {
+            return Some(*span);
+        }
+        path = &path[..path.rfind(['.', '['])?];
+    }
+}
+
+// value positions of a document in any of the formats, JSON is scanned as RON
+pub fn locate_in(format: Format, src: &str) -> HashMap<String, (usize, usize)> {
+    match format {
+        Format::Ron | Format::Json => locate(src),
+        Format::Toml => locate_toml(src),
+        Format::Yaml => locate_yaml(src),
+    }
+}
+
+// Line and column (1-based) of every value of a RON document, keyed by path,
+// e.g. 'tracks[1].ranges[0].start'.
+// Scanning stops at the first syntax error, values before it are still located.
+pub fn locate(src: &str) -> HashMap<String, (usize, usize)> {
+    let mut scanner = Scanner { src, pos: 0, spans: HashMap::new() };
+    scanner.skip_ws();
+    // extensions, #![enable(...)]
+    while scanner.rest().starts_with("#!") {
+        match scanner.rest().find(']') {
+            Some(i) => scanner.pos += i + 1,
+            None => break,
+        }
+        scanner.skip_ws();
+    }
+    scanner.value("");
+    line_cols(src, scanner.spans)
+}
+
+// byte offsets to lines and columns
+fn line_cols(src: &str, spans: HashMap<String, usize>) -> HashMap<String, (usize, usize)> {
+    let line_starts: Vec<usize> =
+        std::iter::once(0).chain(src.match_indices('\n').map(|(i, _)| i + 1)).collect();
+    spans
+        .into_iter()
+        .map(|(path, pos)| {
+            let line = line_starts.partition_point(|&start| start <= pos);
+            let col = src[line_starts[line - 1]..pos].chars().count() + 1;
+            (path, (line, col))
+        })
+        .collect()
+}
+
+// same for TOML, keys are located rather than values, tables at their headers
+pub fn locate_toml(src: &str) -> HashMap<String, (usize, usize)> {
+    let mut spans = HashMap::new();
+    if let Ok(document) = toml_edit::ImDocument::parse(src) {
+        toml_item(document.as_item(), "", &mut spans);
+    }
+    line_cols(src, spans)
+}
+
+fn toml_item(item: &toml_edit::Item, path: &str, spans: &mut HashMap<String, usize>) {
+    if let (false, Some(span)) = (path.is_empty(), item.span()) {
+        spans.entry(path.to_string()).or_insert(span.start);
+    }
+    match item {
+        toml_edit::Item::ArrayOfTables(tables) => {
+            for (i, table) in tables.iter().enumerate() {
+                let path = format!("{}[{}]", path, i);
+                if let Some(span) = table.span() {
+                    spans.entry(path.clone()).or_insert(span.start);
+                }
+                toml_table(table, &path, spans);
+            }
+        }
+        toml_edit::Item::Table(table) => toml_table(table, path, spans),
+        toml_edit::Item::Value(value) => toml_value(value, path, spans),
+        toml_edit::Item::None => {}
+    }
+}
+
+fn toml_table(table: &dyn toml_edit::TableLike, path: &str, spans: &mut HashMap<String, usize>) {
+    for (name, item) in table.iter() {
+        let path = join(path, name);
+        if let Some(span) = table.key(name).and_then(|key| key.span()) {
+            spans.entry(path.clone()).or_insert(span.start);
+        }
+        toml_item(item, &path, spans);
+    }
+}
+
+fn toml_value(value: &toml_edit::Value, path: &str, spans: &mut HashMap<String, usize>) {
+    if let Some(span) = value.span() {
+        spans.entry(path.to_string()).or_insert(span.start);
+    }
+    match value {
+        toml_edit::Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                toml_value(value, &format!("{}[{}]", path, i), spans);
+            }
+        }
+        toml_edit::Value::InlineTable(table) => toml_table(table, path, spans),
+        _ => {}
+    }
+}
+
+// Same for YAML, keys are located rather than values, sequence items at their contents.
+// Only the first document is read, as by the config parser. Complex keys and what's under them
+// are left out, as are the values behind aliases, problems there are reported at the alias.
+pub fn locate_yaml(src: &str) -> HashMap<String, (usize, usize)> {
+    let mut locator = YamlLocator { open: vec![], spans: HashMap::new() };
+    // values before a syntax error are still located
+    let _ = yaml_rust2::parser::Parser::new_from_str(src).load(&mut locator, false);
+    line_cols(src, locator.spans)
+}
+
+// what comes next in an open YAML collection
+enum YamlNext {
+    Key,
+    // value of the key, none for complex keys
+    Value(Option<String>),
+    Item(usize),
+}
+
+// open collections with their paths, none within complex keys
+struct YamlLocator {
+    open: Vec<(Option<String>, YamlNext)>,
+    spans: HashMap<String, usize>,
+}
+
+impl yaml_rust2::parser::MarkedEventReceiver for YamlLocator {
+    fn on_event(&mut self, event: yaml_rust2::Event, mark: yaml_rust2::scanner::Marker) {
+        use yaml_rust2::Event;
+        let collection = match &event {
+            Event::MappingStart(..) => Some(YamlNext::Key),
+            Event::SequenceStart(..) => Some(YamlNext::Item(0)),
+            Event::Scalar(..) | Event::Alias(_) => None,
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.open.pop();
+                return;
+            }
+            _ => return,
+        };
+        // path of the node, the enclosing collection moves on to the next one
+        let path = match self.open.last_mut() {
+            None => Some(String::new()),
+            Some((path, next)) => match next {
+                YamlNext::Key => {
+                    let key = match &event {
+                        Event::Scalar(key, ..) => path.as_deref().map(|path| join(path, key)),
+                        _ => None,
+                    };
+                    if let Some(key) = &key {
+                        self.spans.entry(key.clone()).or_insert(mark.index());
+                    }
+                    // block mappings start after their first key, items are at their contents
+                    if let Some(start) = path.as_ref().and_then(|path| self.spans.get_mut(path)) {
+                        *start = usize::min(*start, mark.index());
+                    }
+                    *next = YamlNext::Value(key);
+                    None
+                }
+                YamlNext::Value(value) => {
+                    let value = value.take();
+                    *next = YamlNext::Key;
+                    value
+                }
+                YamlNext::Item(i) => {
+                    let item = path.as_deref().map(|path| format!("{}[{}]", path, i));
+                    *i += 1;
+                    if let Some(item) = &item {
+                        self.spans.entry(item.clone()).or_insert(mark.index());
+                    }
+                    item
+                }
+            },
+        };
+        if let Some(next) = collection {
+            self.open.push((path, next));
+        }
+    }
+}
+
+#[inline]
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+struct Scanner<'a> {
+    src: &'a str,
+    pos: usize,
+    spans: HashMap<String, usize>,
+}
+
+impl<'a> Scanner<'a> {
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    #[inline]
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    // whitespace and comments
+    fn skip_ws(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                self.pos += trimmed.find("*/").map_or(trimmed.len(), |i| i + 2);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    #[inline]
+    fn ident(&mut self) -> &'a str {
+        self.take_while(|c| c.is_alphanumeric() || c == '_')
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        if self.peek() == Some('r') {
+            // raw string, r#"..."#
+            self.bump();
+            let hashes = self.take_while(|c| c == '#').len();
+            self.bump();
+            let close = format!("\"{}", "#".repeat(hashes));
+            let end = self.rest().find(&close)?;
+            self.pos += end + close.len();
+        } else {
+            let quote = self.peek()?;
+            self.bump();
+            loop {
+                match self.peek()? {
+                    '\\' => {
+                        self.bump();
+                        self.bump();
+                    }
+                    c if c == quote => {
+                        self.bump();
+                        break;
+                    }
+                    _ => self.bump(),
+                }
+            }
+        }
+        Some(self.src[start..self.pos].trim_start_matches('r').trim_matches(['#', '"']).to_string())
+    }
+
+    fn record(&mut self, path: &str, pos: usize) {
+        if !path.is_empty() {
+            self.spans.entry(path.to_string()).or_insert(pos);
+        }
+    }
+
+    fn value(&mut self, path: &str) -> Option<()> {
+        self.skip_ws();
+        self.record(path, self.pos);
+        match self.peek()? {
+            '(' => self.compound(path),
+            '[' => {
+                self.bump();
+                let mut i = 0;
+                loop {
+                    self.skip_ws();
+                    if self.peek()? == ']' {
+                        self.bump();
+                        return Some(());
+                    }
+                    self.value(&format!("{}[{}]", path, i))?;
+                    i += 1;
+                    self.separator(']')?;
+                }
+            }
+            '{' => {
+                self.bump();
+                loop {
+                    self.skip_ws();
+                    match self.peek()? {
+                        '}' => {
+                            self.bump();
+                            return Some(());
+                        }
+                        '"' => {
+                            let key = self.string()?;
+                            self.skip_ws();
+                            (self.peek()? == ':').then_some(())?;
+                            self.bump();
+                            self.value(&join(path, &key))?;
+                        }
+                        // enum keys of RON, plain ones of YAML flow mappings
+                        c if c.is_alphabetic() || c == '_' => {
+                            let key = self.ident();
+                            self.skip_ws();
+                            (self.peek()? == ':').then_some(())?;
+                            self.bump();
+                            self.value(&join(path, key))?;
+                        }
+                        _ => {
+                            self.value("")?;
+                            self.skip_ws();
+                            (self.peek()? == ':').then_some(())?;
+                            self.bump();
+                            self.value("")?;
+                        }
+                    }
+                    self.separator('}')?;
+                }
+            }
+            '"' | '\'' => self.string().map(drop),
+            'r' if matches!(self.rest()[1..].chars().next(), Some('"' | '#')) => {
+                self.string().map(drop)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                self.ident();
+                self.skip_ws();
+                if self.peek() == Some('(') {
+                    self.compound(path)?;
+                }
+                Some(())
+            }
+            _ => {
+                let number = self.take_while(|c| c.is_alphanumeric() || "+-._".contains(c));
+                (!number.is_empty()).then_some(())
+            }
+        }
+    }
+
+    // struct or tuple: (field: value, ...) or (value, ...)
+    fn compound(&mut self, path: &str) -> Option<()> {
+        self.bump();
+        let mut i = 0;
+        loop {
+            self.skip_ws();
+            if self.peek()? == ')' {
+                self.bump();
+                return Some(());
+            }
+            let start = self.pos;
+            let name = self.ident();
+            self.skip_ws();
+            if !name.is_empty() && self.rest().starts_with(':') && !self.rest().starts_with("::") {
+                self.bump();
+                let field = join(path, name);
+                self.record(&field, start);
+                self.value(&field)?;
+            } else {
+                self.pos = start;
+                self.value(&format!("{}[{}]", path, i))?;
+                i += 1;
+            }
+            self.separator(')')?;
+        }
+    }
+
+    // ',' or the closing bracket
+    fn separator(&mut self, close: char) -> Option<()> {
+        self.skip_ws();
+        match self.peek()? {
+            ',' => {
+                self.bump();
+                Some(())
+            }
+            c if c == close => Some(()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::Color;
+
+    use super::*;
+
+    fn config(src: &str) -> Config {
+        let mut config: Config = ron::from_str(src).unwrap();
+        for (index, track) in config.tracks.iter_mut().enumerate() {
+            track.origin.index = index;
+            if let RangeSpec::List(ranges) = &track.range_spec {
+                track.ranges = ranges.clone();
+            }
+            for day in &mut track.overrides {
+                if let RangeSpec::List(ranges) = &day.range_spec {
+                    day.ranges = ranges.clone();
+                }
+            }
+        }
+        config
+    }
+
+    #[test]
+    fn all_problems_reported() {
+        let config = config(
+            r#"(tracks: [
+                (name: "", shortname: "", offset: (24, 0), location: (95.0, 0.0)),
+                (name: "a", shortname: "a", offset: (0, 0),
+                 ranges: [(start:(8,0), end:(12,0), color:White),
+                          (start:(10,0), end:(14,0), color:White),
+                          (start:(20,0), end:(19,0), color:White)]),
+                (name: "a", shortname: "b", offset: (0, 0), ranges: "missing"),
+            ])"#,
+        );
+        let problems = problems(&config);
+        let paths: Vec<(&str, Severity)> =
+            problems.iter().map(|p| (p.path.as_str(), p.severity)).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("tracks[0]", Severity::Error),
+                ("tracks[0].offset", Severity::Error),
+                ("tracks[0].location", Severity::Error),
+                ("tracks[1].ranges[2]", Severity::Error),
+                ("tracks[1].ranges[1]", Severity::Error),
+                ("tracks[2].ranges", Severity::Error),
+                ("tracks[2].name", Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn overrides_checked() {
+        let config = config(
+            r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0),
+                 ranges: [(start:(9,0), end:(17,0), color:White)],
+                 overrides: [(date: "2026-11-27", ranges: [(start:(9,0), end:(13,0), color:White),
+                                                          (start:(12,0), end:(14,0), color:White)]),
+                             (date: "2026-12-24", ranges: "missing"),
+                             (date: "2026-11-27", ranges: [])])])"#,
+        );
+        let problems = problems(&config);
+        let paths: Vec<(&str, Severity)> =
+            problems.iter().map(|p| (p.path.as_str(), p.severity)).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("tracks[0].overrides[0].ranges[1]", Severity::Error),
+                ("tracks[0].overrides[1].ranges", Severity::Error),
+                ("tracks[0].overrides[2].date", Severity::Warning),
+            ]
+        );
+        assert!(ron::from_str::<Config>(
+            r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0), overrides: [(date: "2026-13-01")])])"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn full_day_warning() {
+        let config = config(
+            r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0),
+                 ranges: [(start:(12,0), end:(24,0), color:White),
+                          (start:(0,0), end:(12,0), color:White)])])"#,
+        );
+        let problems = problems(&config);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn palette_names_checked() {
+        let config = config(
+            r#"(palette: {"main": Rgb(250, 200, 0), "alias": main, "Red": Blue},
+                colors: (base: nope),
+                tracks: [(name: "a", shortname: "a", offset: (0, 0),
+                          calendar: (file: "a.ics", color: main, categories: {"on-call": missing}),
+                          ranges: [(start: (1, 0), end: (2, 0), color: main),
+                                   (start: (3, 0), end: (4, 0), color: missing)])])"#,
+        );
+        let problems = problems(&config);
+        let paths: Vec<(Severity, &str)> =
+            problems.iter().map(|p| (p.severity, p.path.as_str())).collect();
+        assert_eq!(
+            paths,
+            [
+                (Severity::Warning, "palette.Red"),
+                (Severity::Error, "palette.alias"),
+                (Severity::Error, "colors.base"),
+                (Severity::Error, "tracks[0].calendar.categories.on-call"),
+                (Severity::Error, "tracks[0].ranges[1].color"),
+            ]
+        );
+    }
+
+    #[test]
+    fn theme_names_checked() {
+        let known = config(
+            r#"(theme: "mine", themes: {"mine": (base: Gray)}, colors: (title: Red),
+                tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        );
+        assert!(problems(&known).is_empty());
+        let theme = known.theme("mine").unwrap();
+        assert_eq!((theme.base, theme.title), (Color::Gray, Color::Red));
+
+        let unknown =
+            config(r#"(theme: "dusk", tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#);
+        let problems = problems(&unknown);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, "theme");
+    }
+
+    #[test]
+    fn key_conflicts_checked() {
+        let keys = config(
+            r#"(keys: {theme: ["T"], seconds: ["t", "Hyper-s"], quit: []},
+                tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        );
+        let problems = problems(&keys);
+        let found: Vec<(&str, Severity)> =
+            problems.iter().map(|p| (p.path.as_str(), p.severity)).collect();
+        assert_eq!(found, [("keys.quit", Severity::Warning), ("keys.seconds", Severity::Error)]);
+        assert_eq!(problems[1].message, r#"unknown key "Hyper-s""#);
+
+        let conflict = config(
+            r#"(keys: {help: ["t"]}, tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        );
+        let problems = super::problems(&conflict);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, "keys.help");
+        assert_eq!(problems[0].message, r#"key "t" is bound to ["theme"] as well"#);
+
+        let quit = config(
+            r#"(keys: {quit: ["Esc"]}, tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        );
+        let problems = super::problems(&quit);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].message, r#"key "Esc" is bound to ["close"] as well"#);
+    }
+
+    #[test]
+    fn overlap_checked() {
+        let overlap = config(
+            r#"(overlap: (tracks: ["a", "nope"], range: main),
+                tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        );
+        let paths: Vec<String> = problems(&overlap).into_iter().map(|p| p.path).collect();
+        assert_eq!(paths, ["overlap.tracks[1]", "overlap.range"]);
+    }
+
+    #[test]
+    fn locate_values() {
+        let src = "// comment\n(tracks: [\n  (name: \"a\", offset: (1, 0),\n   ranges: [(start:(8,0))])\n])";
+        let spans = locate(src);
+        assert_eq!(spans["tracks"], (2, 2));
+        assert_eq!(spans["tracks[0]"], (3, 3));
+        assert_eq!(spans["tracks[0].offset"], (3, 15));
+        assert_eq!(spans["tracks[0].ranges[0].start"], (4, 14));
+        assert_eq!(span_of(&spans, "tracks[0].ranges[0].end"), Some((4, 13)));
+    }
+
+    #[test]
+    fn locate_maps_and_enums() {
+        let src = "#![enable(implicit_some)]\n(templates: {\"day\": [(color: Rgb(1, 2, 3))]}, x: r#\"s\"#)";
+        let spans = locate(src);
+        assert_eq!(spans["templates.day[0].color"], (2, 23));
+        assert_eq!(spans["x"], (2, 47));
+    }
+
+    #[test]
+    fn locate_other_formats() {
+        let json = "{\"tracks\": [\n  {\"name\": \"a\",\n   \"offset\": [1, 0]}]}";
+        let spans = locate_in(Format::Json, json);
+        assert_eq!(spans["tracks[0]"], (2, 3));
+        assert_eq!(spans["tracks[0].offset"], (3, 14));
+
+        let toml = "theme = \"dark\"\n[keys]\nquit = [\"q\"]\n\n[[tracks]]\nname = \"a\"\n\n[[tracks]]\nname = \"b\"\noffset = \"+01:00\"\nranges = [{ start = \"09:00\", end = \"17:00\" }]\n";
+        let spans = locate_in(Format::Toml, toml);
+        assert_eq!(spans["theme"], (1, 1));
+        assert_eq!(spans["keys.quit[0]"], (3, 9));
+        assert_eq!(spans["tracks[1]"], (8, 1));
+        assert_eq!(spans["tracks[1].offset"], (10, 1));
+        assert_eq!(spans["tracks[1].ranges[0].end"], (11, 30));
+
+        let yaml = "theme: dark\ntracks:\n- name: a\n  offset: [1, 0]\n  description: |\n    a: b\n- name: b\n  ranges:\n    - {start: \"09:00\", end: \"17:00\"}\n    - start: \"17:00\"\n      end: \"18:00\" # evening\nkeys: {quit: [\"q\"]}\n";
+        let spans = locate_in(Format::Yaml, yaml);
+        assert_eq!(spans["theme"], (1, 1));
+        assert_eq!(spans["tracks[0]"], (3, 3));
+        assert_eq!(spans["tracks[0].offset"], (4, 3));
+        assert_eq!(spans["tracks[0].offset[1]"], (4, 15));
+        assert!(!spans.contains_key("tracks[0].a"));
+        assert_eq!(spans["tracks[1].name"], (7, 3));
+        assert_eq!(spans["tracks[1].ranges[0].end"], (9, 24));
+        assert_eq!(spans["tracks[1].ranges[1].end"], (11, 7));
+        assert_eq!(spans["keys"], (12, 1));
+        assert_eq!(spans["keys.quit"], (12, 8));
+        assert_eq!(spans["keys.quit[0]"], (12, 15));
+    }
+
+    #[test]
+    fn locate_yaml_constructs() {
+        let yaml = "templates:\n  day: &day\n    - {start: \"09:00\", end: \"17:00\"}\n\
+? [complex, key]\n: {name: x}\n\
+tracks:\n  - name: a\n    ranges: *day\n  - &b\n    name: b\n  - *b\n\
+---\ntheme: dark\n";
+        let spans = locate_yaml(yaml);
+        assert_eq!(spans["templates.day[0].end"], (3, 24));
+        // what's behind an alias is reported at the alias
+        assert_eq!(spans["tracks[0].ranges"], (8, 5));
+        assert!(!spans.contains_key("tracks[0].ranges[0]"));
+        assert_eq!(span_of(&spans, "tracks[0].ranges[0].end"), Some((8, 5)));
+        assert_eq!(spans["tracks[1].name"], (10, 5));
+        assert_eq!(spans["tracks[2]"], (11, 5));
+        assert!(!spans.contains_key("tracks[2].name"));
+        // complex keys and the next documents are left out
+        assert!(!spans.keys().any(|path| path.contains("name") && !path.starts_with("tracks")));
+        assert!(!spans.contains_key("theme"));
+        // up to a syntax error
+        let spans = locate_yaml("tracks:\n  - name: a\n  - name: [b\n");
+        assert_eq!(spans["tracks[0].name"], (2, 5));
+    }
+
+    #[test]
+    fn positions_in_every_format() {
+        let dir = std::env::temp_dir().join(format!("mvtime-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, src, position) in [
+            ("bad.ron", "(tracks: [\n  (name: \"a\", shortname: \"a\", offset: (25, 0))])", "2:31"),
+            ("bad.toml", "[[tracks]]\nname = \"a\"\nshortname = \"a\"\noffset = [25, 0]\n", "4:1"),
+            ("bad.yaml", "tracks:\n  - name: a\n    shortname: a\n    offset: [25, 0]\n", "4:5"),
+            (
+                "bad.json",
+                "{\"tracks\": [\n  {\"name\": \"a\", \"shortname\": \"a\", \"offset\": [25, 0]}]}",
+                "2:45",
+            ),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, src).unwrap();
+            let mut out = vec![];
+            run(path.clone(), &mut out).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            let expected = format!("{}:{}: error:", path.display(), position);
+            assert!(out.contains(&expected), "{}", out);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn calendars_reported_with_the_rest() {
+        let dir = std::env::temp_dir().join(format!("mvtime-check-ics-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("main.ron");
+        std::fs::write(
+            &path,
+            "(theme: \"nope\", tracks: [\n  (name: \"a\", shortname: \"a\", offset: (0, 0), \
+calendar: (file: \"missing.ics\")),\n  (name: \"b\", shortname: \"b\", offset: (1, 0), \
+calendar: (file: \"bad.ics\"))])",
+        )
+        .unwrap();
+        let event = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:2026\nEND:VEVENT\nEND:VCALENDAR\n";
+        std::fs::write(dir.join("bad.ics"), event).unwrap();
+        let mut out = vec![];
+        let errors = run(path.clone(), &mut out).unwrap();
+        let converted = config::convert(path.clone(), &dir.join("out.json"), false);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(errors, 3, "{}", out);
+        assert!(out.contains(":2:58: error: track \"a\": Failed to read calendar"), "{}", out);
+        assert!(out.contains(":3:58: error: track \"b\": Failed to parse calendar"), "{}", out);
+        assert!(out.contains("line 3: invalid date"), "{}", out);
+        // calendars are no concern of the format
+        assert!(converted.is_ok());
+    }
+}
@@ -0,0 +1,173 @@
+use tui::style::Color;
+
+// Colors the terminal can show, in the ascending order of richness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    Mono,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+// xterm defaults of the 16 basic colors, in the ANSI index order
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// levels of the 6x6x6 color cube of the 256-color palette
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+    // `--color` values, None for "auto"
+    pub const NAMES: [&'static str; 5] = ["auto", "truecolor", "256", "16", "never"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truecolor" => Some(ColorSupport::TrueColor),
+            "256" => Some(ColorSupport::Ansi256),
+            "16" => Some(ColorSupport::Ansi16),
+            "never" => Some(ColorSupport::Mono),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::detect(|name| std::env::var(name).ok())
+    }
+
+    // `NO_COLOR` (https://no-color.org) wins, then `COLORTERM` and `TERM` are looked at
+    pub fn detect<F: Fn(&str) -> Option<String>>(var: F) -> Self {
+        if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColorSupport::Mono;
+        }
+        let colorterm = var("COLORTERM").unwrap_or_default().to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorSupport::TrueColor;
+        }
+        let term = var("TERM").unwrap_or_default().to_lowercase();
+        match term.as_str() {
+            // Windows consoles don't set TERM, but handle RGB since Windows 10
+            "" if cfg!(windows) => ColorSupport::TrueColor,
+            "dumb" => ColorSupport::Mono,
+            t if t.contains("truecolor") || t.contains("24bit") || t.contains("direct") => {
+                ColorSupport::TrueColor
+            }
+            t if t.contains("256") => ColorSupport::Ansi256,
+            _ => ColorSupport::Ansi16,
+        }
+    }
+
+    // nearest color the terminal can show, `Reset` in mono
+    pub fn map(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::Mono, _) => Color::Reset,
+            (ColorSupport::TrueColor, color) => color,
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest256((r, g, b))),
+            (ColorSupport::Ansi16, Color::Rgb(r, g, b)) => nearest16((r, g, b)),
+            (ColorSupport::Ansi16, Color::Indexed(i)) if i < 16 => ANSI16[i as usize].0,
+            (ColorSupport::Ansi16, Color::Indexed(i)) => nearest16(indexed_rgb(i)),
+            (_, color) => color,
+        }
+    }
+}
+
+#[inline]
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+#[inline]
+fn nearest16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, c)| distance(rgb, *c))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+// nearest of the color cube and the gray ramp entries
+fn nearest256((r, g, b): (u8, u8, u8)) -> u8 {
+    let level = |v: u8| CUBE.iter().enumerate().min_by_key(|(_, l)| v.abs_diff(**l)).unwrap().0;
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    let avg = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray = 232 + (avg.saturating_sub(3) / 10).min(23) as usize;
+
+    let cube_rgb = (CUBE[ri], CUBE[gi], CUBE[bi]);
+    if distance((r, g, b), indexed_rgb(gray as u8)) < distance((r, g, b), cube_rgb) {
+        gray as u8
+    } else {
+        cube as u8
+    }
+}
+
+// RGB value of the 256-color palette entry
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI16[i as usize].1,
+        16..=231 => {
+            let i = (i - 16) as usize;
+            (CUBE[i / 36], CUBE[i / 6 % 6], CUBE[i % 6])
+        }
+        _ => {
+            let v = 8 + 10 * (i - 232);
+            (v, v, v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::Color;
+
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorSupport {
+        ColorSupport::detect(|name| {
+            vars.iter().find(|(n, _)| *n == name).map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn support_detected_from_env() {
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorSupport::Ansi256);
+        assert_eq!(detect(&[("TERM", "screen")]), ColorSupport::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorSupport::Mono);
+        assert_eq!(detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]), ColorSupport::Mono);
+        assert_eq!(detect(&[("NO_COLOR", ""), ("TERM", "xterm-256color")]), ColorSupport::Ansi256);
+    }
+
+    #[test]
+    fn rgb_downsampled() {
+        let orange = Color::Rgb(250, 200, 0);
+        assert_eq!(ColorSupport::TrueColor.map(orange), orange);
+        assert_eq!(ColorSupport::Ansi256.map(orange), Color::Indexed(220));
+        assert_eq!(ColorSupport::Ansi256.map(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(ColorSupport::Ansi16.map(orange), Color::Yellow);
+        assert_eq!(ColorSupport::Ansi16.map(Color::Indexed(196)), Color::LightRed);
+        assert_eq!(ColorSupport::Ansi16.map(Color::Indexed(1)), Color::Red);
+        assert_eq!(ColorSupport::Ansi16.map(Color::Blue), Color::Blue);
+        assert_eq!(ColorSupport::Mono.map(Color::Blue), Color::Reset);
+    }
+}
@@ -1,11 +1,27 @@
-use chrono::{DateTime, NaiveTime, Timelike, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Timelike, Utc};
 use directories::ProjectDirs;
 use eyre::{Context, Result};
-use ron;
-use serde::{Deserialize, Deserializer};
-use std::path::PathBuf;
+use serde::{
+    de::{self, EnumAccess, SeqAccess, VariantAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+};
 use tui::style::Color;
 
+use crate::{
+    check::{self, Severity},
+    format::{self, Format},
+    ics::{self, Event},
+    keys::Action,
+    solar::Daylight,
+    theme::{self, BarGlyph, BarStyle, Theme},
+    to_minutes, MINUTES_PER_DAY,
+};
+
 // workaround to get rid of 'Some(bool)' in ron files
 fn deserialize_option_bool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
 where
@@ -15,54 +31,380 @@ where
     Ok(Some(value))
 }
 
-#[derive(Debug, Default, Deserialize)]
+// same for any other optional value, so 'base: Gray' is written instead of 'base: Some(Gray)'
+pub(crate) fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+// "09:30", "9:30pm", "9pm", "24:00" => (hour, minute), values aren't range checked except for 12-hour clock
+pub fn parse_time(s: &str) -> Result<(u16, u16), String> {
+    let err = || format!("invalid time {:?}, expected 'HH:MM' or 'H[:MM]am|pm'", s);
+    let lower = s.trim().to_lowercase();
+    let (clock, meridiem) = match lower.strip_suffix("am").or_else(|| lower.strip_suffix("pm")) {
+        Some(clock) => (clock.trim_end(), Some(lower.ends_with("pm"))),
+        None => (lower.as_str(), None),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute),
+        Some(_) => return Err(err()),
+        None if meridiem.is_some() => (clock, "0"),
+        None => return Err(err()),
+    };
+    let digits = |v: &str| !v.is_empty() && v.len() <= 2 && v.bytes().all(|b| b.is_ascii_digit());
+    if !digits(hour) || !digits(minute) {
+        return Err(err());
+    }
+    let (hour, minute): (u16, u16) =
+        (hour.parse().map_err(|_| err())?, minute.parse().map_err(|_| err())?);
+    match meridiem {
+        Some(_) if hour == 0 || hour > 12 => Err(err()),
+        // 12am is the midnight, 12pm is the noon
+        Some(pm) => Ok((hour % 12 + 12 * pm as u16, minute)),
+        None => Ok((hour, minute)),
+    }
+}
+
+// "UTC-05:00", "+05:45", "GMT+3", "-00:30" => (hour, minute), both of the same sign
+pub fn parse_offset(s: &str) -> Result<(i16, i16), String> {
+    let err = || format!("invalid UTC offset {:?}, expected '[UTC]+HH:MM' or '[UTC]-HH:MM'", s);
+    let upper = s.trim().to_uppercase();
+    let rest = upper.strip_prefix("UTC").or_else(|| upper.strip_prefix("GMT")).unwrap_or(&upper);
+    if rest.is_empty() {
+        return Ok((0, 0));
+    }
+    let (sign, rest) = match rest.as_bytes()[0] {
+        b'-' => (-1, &rest[1..]),
+        b'+' => (1, &rest[1..]),
+        _ => (1, rest),
+    };
+    let (hour, minute) = rest.split_once(':').unwrap_or((rest, "00"));
+    let digits = |v: &str| !v.is_empty() && v.len() <= 2 && v.bytes().all(|b| b.is_ascii_digit());
+    if !digits(hour) || minute.len() != 2 || !digits(minute) {
+        return Err(err());
+    }
+    let (hour, minute): (i16, i16) =
+        (hour.parse().map_err(|_| err())?, minute.parse().map_err(|_| err())?);
+    Ok((sign * hour, sign * minute))
+}
+
+// "UTC+05:30" form of the offset
+pub fn fmt_offset((hour, minute): (i16, i16)) -> String {
+    let sign = if hour < 0 || minute < 0 { '-' } else { '+' };
+    format!("UTC{}{:02}:{:02}", sign, hour.abs(), minute.abs())
+}
+
+// time as (hour, minute) tuple or a string, see `parse_time`
+fn deserialize_time<'de, D>(deserializer: D) -> Result<(u16, u16), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct TimeVisitor;
+
+    impl<'de> Visitor<'de> for TimeVisitor {
+        type Value = (u16, u16);
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("(hour, minute) or a time string like \"09:30\", \"9:30pm\"")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            parse_time(s).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let hour = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let minute = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+            Ok((hour, minute))
+        }
+    }
+
+    deserializer.deserialize_any(TimeVisitor)
+}
+
+// Offset as (hour, minute) tuple or a string, see `parse_offset`.
+// Tuple minutes take the sign of the hour, unless the hour is 0, so (-5, 30) and (-5, -30)
+// are both -05:30, and (0, -30) is -00:30.
+fn deserialize_offset<'de, D>(deserializer: D) -> Result<(i16, i16), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct OffsetVisitor;
+
+    impl<'de> Visitor<'de> for OffsetVisitor {
+        type Value = (i16, i16);
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("(hour, minute) or an offset string like \"UTC-05:00\", \"+05:45\"")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            parse_offset(s).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let hour: i16 =
+                seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let minute: i16 =
+                seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+            match hour.signum() {
+                0 => Ok((hour, minute)),
+                sign => Ok((hour, minute.abs() * sign)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(OffsetVisitor)
+}
+
+// "2026-11-27" date
+fn deserialize_date<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|_| de::Error::custom(format!("invalid date {:?}, expected 'YYYY-MM-DD'", s)))
+}
+
+fn serialize_date<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&date.format("%Y-%m-%d"))
+}
+
+// and back, used along with `skip_serializing_if = "Option::is_none"`
+fn serialize_option_bool<S: Serializer>(
+    value: &Option<bool>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bool(value.unwrap_or_default())
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Config {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default, skip_serializing_if = "Colors::is_empty")]
     pub colors: Colors,
+    // name of a preset or of a `themes` entry, "dark" if empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub theme: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, Theme>,
+    // sub-cell precision bars drawn with eighth blocks
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hires: bool,
+    // time the bars cover, "12h", "24h", "48h" or "7d"
+    #[serde(default, skip_serializing_if = "Span::is_day")]
+    pub span: Span,
+    // bar style override of the theme one
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bar: Option<BarStyle>,
+    // named colors, ranges and `colors` may use the names instead of colors
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub palette: BTreeMap<String, ColorSpec>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, Vec<TimeRange>>,
+    // tracks and range color of the shared windows highlighted in live mode
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub overlap: Option<Overlap>,
+    // live mode key bindings, actions missing here keep their default keys
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<Action, Vec<String>>,
+    #[serde(default)]
     pub tracks: Vec<TimeTrack>,
+    // config file followed by all the included files
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct Colors {
-    #[serde(default = "default_base_color")]
-    pub base: Color,
-    #[serde(default = "default_fill_fg_color")]
-    pub fill_fg: Color,
-    #[serde(default = "default_clock_color")]
-    pub clock: Color,
-    #[serde(default = "default_title_color")]
-    pub title: Color,
+// Tracks by name or shortname, all of them if empty, and the color of their ranges to overlap,
+// any range if not set
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Overlap {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tracks: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<ColorSpec>,
 }
 
-#[inline(always)]
-fn default_base_color() -> Color {
-    Color::DarkGray
+impl Config {
+    // presets followed by the config themes, a config theme named after a preset replaces it
+    pub fn theme_names(&self) -> Vec<&str> {
+        let presets = theme::PRESETS.iter().map(|(name, _)| *name);
+        let custom = self.themes.keys().map(String::as_str);
+        let mut names: Vec<&str> = presets.chain(custom).collect();
+        let mut seen = vec![];
+        names.retain(|name| {
+            let first = !seen.contains(name);
+            seen.push(*name);
+            first
+        });
+        names
+    }
+
+    // theme with the `colors` and `bar` overrides applied
+    pub fn theme(&self, name: &str) -> Option<Theme> {
+        let mut theme = self.themes.get(name).copied().or_else(|| theme::preset(name))?;
+        theme.bar = self.bar.unwrap_or(theme.bar);
+        let colors = &self.colors;
+        for (spec, color) in [
+            (&colors.base, &mut theme.base),
+            (&colors.fill_fg, &mut theme.fill_fg),
+            (&colors.clock, &mut theme.clock),
+            (&colors.title, &mut theme.title),
+        ] {
+            *color = spec.as_ref().and_then(|spec| self.color(spec)).unwrap_or(*color);
+        }
+        Some(theme)
+    }
+
+    // palette entries are plain colors, they can't reference each other
+    pub fn color(&self, spec: &ColorSpec) -> Option<Color> {
+        match spec {
+            ColorSpec::Color(color) => Some(*color),
+            ColorSpec::Palette(name) => match self.palette.get(name) {
+                Some(ColorSpec::Color(color)) => Some(*color),
+                _ => None,
+            },
+        }
+    }
+
+    #[inline]
+    pub fn theme_name(&self) -> &str {
+        if self.theme.is_empty() {
+            theme::DEFAULT_THEME
+        } else {
+            &self.theme
+        }
+    }
 }
-#[inline(always)]
-fn default_fill_fg_color() -> Color {
-    Color::Black
+
+// Overrides of the theme colors, kept for the configs written before themes
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Colors {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub base: Option<ColorSpec>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fill_fg: Option<ColorSpec>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub clock: Option<ColorSpec>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub title: Option<ColorSpec>,
 }
-#[inline(always)]
-fn default_title_color() -> Color {
-    Color::Reset
+
+impl Colors {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.base.is_none()
+            && self.fill_fg.is_none()
+            && self.clock.is_none()
+            && self.title.is_none()
+    }
 }
-#[inline(always)]
-fn default_clock_color() -> Color {
-    Color::Reset
+
+// Time the bars cover, a half of the day with the current time, the day, the day along with the next one,
+// or the week from Monday
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Span {
+    HalfDay,
+    #[default]
+    Day,
+    TwoDays,
+    Week,
 }
 
-impl Default for Colors {
-    fn default() -> Self {
-        Colors {
-            base: default_base_color(),
-            fill_fg: default_fill_fg_color(),
-            title: default_title_color(),
-            clock: default_clock_color(),
+impl Span {
+    // shortest to longest, zoom steps
+    pub const ALL: [Span; 4] = [Span::HalfDay, Span::Day, Span::TwoDays, Span::Week];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Span::HalfDay => "12h",
+            Span::Day => "24h",
+            Span::TwoDays => "48h",
+            Span::Week => "7d",
+        }
+    }
+
+    #[inline]
+    pub fn is_day(&self) -> bool {
+        *self == Span::Day
+    }
+
+    // Start of the span in minutes since the local midnight, negative for the days before,
+    // and its length in minutes, given the local hour and weekday.
+    pub fn window(self, hour: u16, weekday: chrono::Weekday) -> (i32, u16) {
+        let day = i32::from(MINUTES_PER_DAY);
+        match self {
+            Span::HalfDay if hour >= 12 => (day / 2, MINUTES_PER_DAY / 2),
+            Span::HalfDay => (0, MINUTES_PER_DAY / 2),
+            Span::Day => (0, MINUTES_PER_DAY),
+            Span::TwoDays => (0, MINUTES_PER_DAY * 2),
+            Span::Week => (-(weekday.num_days_from_monday() as i32) * day, MINUTES_PER_DAY * 7),
         }
     }
+
+    // the next shorter span, or the next longer one
+    pub fn zoom(self, zoom_in: bool) -> Self {
+        let i = Span::ALL.iter().position(|s| *s == self).unwrap_or(1);
+        match zoom_in {
+            true => Span::ALL[i.saturating_sub(1)],
+            false => Span::ALL[usize::min(i + 1, Span::ALL.len() - 1)],
+        }
+    }
+}
+
+impl TryFrom<String> for Span {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        let names: Vec<&str> = Span::ALL.iter().map(|s| s.name()).collect();
+        Span::ALL
+            .into_iter()
+            .find(|s| s.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("unknown span {:?}, expected one of {:?}", name, names))
+    }
 }
 
-#[derive(Debug, Default, Deserialize)]
+impl From<Span> for String {
+    fn from(span: Span) -> Self {
+        span.name().to_string()
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct TimeLabel {
     #[serde(default)]
     pub seconds: bool,
@@ -72,40 +414,107 @@ pub struct TimeLabel {
     pub fill: bool,
     #[serde(default)]
     pub use_range_color: bool,
+    // percentage of the current range passed, shown after the time
+    #[serde(default)]
+    pub progress: bool,
+}
+
+impl TimeLabel {
+    // _ hh : mm _, _ hh : mm : ss _, plus 'nn% ' with the progress
+    #[inline]
+    pub fn width(&self) -> u16 {
+        7 + self.seconds as u16 * 3 + self.progress as u16 * 4
+    }
+}
+
+#[inline(always)]
+fn default_range_color() -> Color {
+    Color::Reset
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TimeRange {
+    #[serde(deserialize_with = "deserialize_time")]
     pub start: (u16, u16),
+    #[serde(deserialize_with = "deserialize_time")]
     pub end: (u16, u16),
+    #[serde(rename = "color")]
+    pub color_spec: ColorSpec,
+    // color with the palette name resolved
+    #[serde(skip, default = "default_range_color")]
     pub color: Color,
-    #[serde(default, deserialize_with = "deserialize_option_bool")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_bool",
+        serialize_with = "serialize_option_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub fill: Option<bool>,
-    #[serde(default, deserialize_with = "deserialize_option_bool")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_bool",
+        serialize_with = "serialize_option_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub use_range_color: Option<bool>,
-    #[serde(default, deserialize_with = "deserialize_option_bool")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_bool",
+        serialize_with = "serialize_option_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub blink: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub glyph: Option<BarGlyph>,
+    // inserted by `fill_gaps`, takes the theme base color
+    #[serde(skip)]
+    pub gap: bool,
+    // generated from the track location, takes the theme night or twilight color
+    #[serde(skip)]
+    pub daylight: Option<Daylight>,
 }
 
 impl TimeRange {
     pub fn new(start: (u16, u16), end: (u16, u16), color: Color) -> Self {
-        TimeRange { start, end, color, fill: None, use_range_color: None, blink: None }
+        TimeRange {
+            start,
+            end,
+            color_spec: ColorSpec::Color(color),
+            color,
+            fill: None,
+            use_range_color: None,
+            blink: None,
+            glyph: None,
+            gap: false,
+            daylight: None,
+        }
     }
 }
 
 impl Default for TimeRange {
     fn default() -> Self {
-        Self {
-            start: (0, 0),
-            end: (24, 0),
-            color: Color::DarkGray,
-            fill: None,
-            use_range_color: None,
-            blink: None,
-        }
+        TimeRange::new((0, 0), (24, 0), Color::DarkGray)
     }
 }
 impl TimeRange {
+    pub fn gap(start: (u16, u16), end: (u16, u16), base: Color) -> Self {
+        TimeRange { gap: true, ..TimeRange::new(start, end, base) }
+    }
+
+    pub fn daylight(start: (u16, u16), end: (u16, u16), kind: Daylight, theme: &Theme) -> Self {
+        TimeRange { daylight: Some(kind), ..TimeRange::new(start, end, theme.daylight(kind)) }
+    }
+
+    // not one of the config ranges
+    #[inline]
+    pub fn auto(&self) -> bool {
+        self.gap || self.daylight.is_some()
+    }
+
     pub fn start(mut self, start: (u16, u16)) -> Self {
         self.start = start;
         self
@@ -116,29 +525,374 @@ impl TimeRange {
     }
 
     pub fn color(mut self, color: Color) -> Self {
+        self.color_spec = ColorSpec::Color(color);
         self.color = color;
         self
     }
 }
 
-#[derive(Debug, Deserialize)]
+// Color as written in the config: a color, a hex string or a `palette` name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorSpec {
+    Color(Color),
+    Palette(String),
+}
+
+impl Default for ColorSpec {
+    fn default() -> Self {
+        ColorSpec::Color(Color::Reset)
+    }
+}
+
+const COLOR_NAMES: [(&str, Color); 17] = [
+    ("Reset", Color::Reset),
+    ("Black", Color::Black),
+    ("Red", Color::Red),
+    ("Green", Color::Green),
+    ("Yellow", Color::Yellow),
+    ("Blue", Color::Blue),
+    ("Magenta", Color::Magenta),
+    ("Cyan", Color::Cyan),
+    ("Gray", Color::Gray),
+    ("DarkGray", Color::DarkGray),
+    ("LightRed", Color::LightRed),
+    ("LightGreen", Color::LightGreen),
+    ("LightYellow", Color::LightYellow),
+    ("LightBlue", Color::LightBlue),
+    ("LightMagenta", Color::LightMagenta),
+    ("LightCyan", Color::LightCyan),
+    ("White", Color::White),
+];
+
+#[inline]
+pub fn is_color_name(name: &str) -> bool {
+    COLOR_NAMES.iter().any(|(n, _)| *n == name)
+}
+
+// "#fac800" or "#fc0" => Rgb(250, 200, 0)
+pub fn parse_hex(s: &str) -> Result<Color, String> {
+    let err = || format!("invalid hex color {:?}, expected '#rrggbb' or '#rgb'", s);
+    let hex = s.strip_prefix('#').ok_or_else(err)?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(err());
+    }
+    let channel = |i: usize, len: usize| {
+        let v = u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).unwrap_or_default();
+        if len == 1 {
+            v * 17
+        } else {
+            v
+        }
+    };
+    match hex.len() {
+        3 => Ok(Color::Rgb(channel(0, 1), channel(1, 1), channel(2, 1))),
+        6 => Ok(Color::Rgb(channel(0, 2), channel(1, 2), channel(2, 2))),
+        _ => Err(err()),
+    }
+}
+
+impl ColorSpec {
+    // color name, hex string, or a palette name otherwise
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        if let Some((_, color)) = COLOR_NAMES.iter().find(|(n, _)| *n == name) {
+            Ok(ColorSpec::Color(*color))
+        } else if name.starts_with('#') {
+            parse_hex(name).map(ColorSpec::Color)
+        } else {
+            Ok(ColorSpec::Palette(name.to_string()))
+        }
+    }
+}
+
+// Colors are enums, so palette names are written as variants, e.g. 'color: main' in RON
+// and 'color: "main"' elsewhere. RON can't have a string in place of an enum,
+// so hex is 'Hex("#fac800")' there and just "#fac800" in the other formats.
+impl<'de> Deserialize<'de> for ColorSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VariantName(String);
+
+        impl<'de> Deserialize<'de> for VariantName {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct NameVisitor;
+
+                impl<'de> Visitor<'de> for NameVisitor {
+                    type Value = VariantName;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a color name")
+                    }
+
+                    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                        Ok(VariantName(s.to_string()))
+                    }
+                }
+
+                deserializer.deserialize_identifier(NameVisitor)
+            }
+        }
+
+        struct RgbVisitor;
+
+        impl<'de> Visitor<'de> for RgbVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("Rgb(red, green, blue)")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut channel =
+                    |i| seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self));
+                Ok(Color::Rgb(channel(0)?, channel(1)?, channel(2)?))
+            }
+        }
+
+        struct ColorSpecVisitor;
+
+        impl<'de> Visitor<'de> for ColorSpecVisitor {
+            type Value = ColorSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a color, a hex color string or a palette name")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                ColorSpec::from_name(s).map_err(E::custom)
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (VariantName(name), variant) = data.variant()?;
+                match name.as_str() {
+                    "Rgb" => variant.tuple_variant(3, RgbVisitor).map(ColorSpec::Color),
+                    "Indexed" => {
+                        variant.newtype_variant().map(|i| ColorSpec::Color(Color::Indexed(i)))
+                    }
+                    "Hex" => {
+                        let hex: String = variant.newtype_variant()?;
+                        parse_hex(&hex).map(ColorSpec::Color).map_err(de::Error::custom)
+                    }
+                    "Palette" => variant.newtype_variant().map(ColorSpec::Palette),
+                    name => {
+                        variant.unit_variant()?;
+                        ColorSpec::from_name(name).map_err(de::Error::custom)
+                    }
+                }
+            }
+        }
+
+        const VARIANTS: [&str; 21] = [
+            "Reset",
+            "Black",
+            "Red",
+            "Green",
+            "Yellow",
+            "Blue",
+            "Magenta",
+            "Cyan",
+            "Gray",
+            "DarkGray",
+            "LightRed",
+            "LightGreen",
+            "LightYellow",
+            "LightBlue",
+            "LightMagenta",
+            "LightCyan",
+            "White",
+            "Rgb",
+            "Indexed",
+            "Hex",
+            "Palette",
+        ];
+        deserializer.deserialize_enum("Color", &VARIANTS, ColorSpecVisitor)
+    }
+}
+
+impl Serialize for ColorSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ColorSpec::Color(color) => color.serialize(serializer),
+            // serde wants a 'static variant name, so RON gets 'Palette("main")' and the rest a string
+            ColorSpec::Palette(name) if format::writing_ron() => {
+                serializer.serialize_newtype_variant("Color", 20, "Palette", name)
+            }
+            ColorSpec::Palette(name) => serializer.serialize_str(name),
+        }
+    }
+}
+
+// plain color, a color or a hex string, but not a palette name
+pub(crate) fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match ColorSpec::deserialize(deserializer)? {
+        ColorSpec::Color(color) => Ok(color),
+        ColorSpec::Palette(name) => Err(de::Error::custom(format!(
+            "unknown color {:?}, palette names can't be used here",
+            name
+        ))),
+    }
+}
+
+pub(crate) fn deserialize_some_color<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_color(deserializer).map(Some)
+}
+
+// Track ranges as written in the config: either a list of ranges or a template name
+#[derive(Debug, Clone)]
+pub enum RangeSpec {
+    List(Vec<TimeRange>),
+    Template(String),
+}
+
+impl Default for RangeSpec {
+    fn default() -> Self {
+        RangeSpec::List(vec![])
+    }
+}
+
+impl<'de> Deserialize<'de> for RangeSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RangeSpecVisitor;
+
+        impl<'de> Visitor<'de> for RangeSpecVisitor {
+            type Value = RangeSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of time ranges or a template name")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                Ok(RangeSpec::Template(name.to_string()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut ranges = vec![];
+                while let Some(range) = seq.next_element()? {
+                    ranges.push(range);
+                }
+                Ok(RangeSpec::List(ranges))
+            }
+        }
+
+        deserializer.deserialize_any(RangeSpecVisitor)
+    }
+}
+
+impl Serialize for RangeSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RangeSpec::List(ranges) => ranges.serialize(serializer),
+            RangeSpec::Template(name) => serializer.serialize_str(name),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TimeTrack {
     pub name: String,
     pub shortname: String,
+    // hour and minute are of the same sign
+    #[serde(deserialize_with = "deserialize_offset")]
     pub offset: (i16, i16),
     #[serde(default)]
     pub show_badge: bool,
     #[serde(default)]
     pub time_label: TimeLabel,
-    #[serde(default)]
+    #[serde(default, rename = "ranges")]
+    pub range_spec: RangeSpec,
+    // (latitude, longitude) in degrees, night and twilight are shown where there are no ranges
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub location: Option<(f64, f64)>,
+    // iCalendar file with the events shown over the ranges
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub calendar: Option<Calendar>,
+    // ranges of the single local dates in place of the usual ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<Override>,
+    // local date the ranges are computed for, see `TimeTrack::dated`
+    #[serde(skip)]
+    pub date: Option<NaiveDate>,
+    // usual ranges of a dated track, `ranges` are the ones of `date`
+    #[serde(skip)]
+    pub regular: Vec<TimeRange>,
+    // ranges with the templates resolved
+    #[serde(skip)]
     pub ranges: Vec<TimeRange>,
+    #[serde(skip)]
+    pub origin: Origin,
+}
+
+// ranges of a date, e.g. an early close or an on-call shift
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Override {
+    #[serde(deserialize_with = "deserialize_date", serialize_with = "serialize_date")]
+    pub date: NaiveDate,
+    #[serde(default, rename = "ranges")]
+    pub range_spec: RangeSpec,
+    // ranges with the templates resolved
+    #[serde(skip)]
+    pub ranges: Vec<TimeRange>,
+}
+
+// Events of a local .ics file, colored by their categories
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Calendar {
+    // path relative to the config file
+    pub file: String,
+    // color of the events with none of the `categories`
+    #[serde(default)]
+    pub color: ColorSpec,
+    // category name => color, the first of the event categories found here is taken, case is ignored
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, ColorSpec>,
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(skip)]
+    pub events: Vec<Event>,
+    // color of each of the `events`
+    #[serde(skip)]
+    pub colors: Vec<Color>,
+}
+
+impl Calendar {
+    fn event_color(&self, event: &Event, config: &Config) -> Color {
+        let category = event.categories.iter().find_map(|name| {
+            self.categories.iter().find(|(c, _)| c.eq_ignore_ascii_case(name)).map(|(_, spec)| spec)
+        });
+        config.color(category.unwrap_or(&self.color)).unwrap_or(Color::Reset)
+    }
+}
+
+// config file the track is defined in and its position in the file's track list
+#[derive(Debug, Default, Clone)]
+pub struct Origin {
+    pub file: PathBuf,
+    pub index: usize,
 }
 
 impl TimeTrack {
     #[inline]
     pub fn offset(&self) -> chrono::Duration {
         chrono::Duration::hours(self.offset.0 as i64)
-            + chrono::Duration::minutes((self.offset.1.abs() * self.offset.0.signum()) as i64)
+            + chrono::Duration::minutes(self.offset.1 as i64)
+    }
+
+    // whether the ranges depend on the local date
+    #[inline]
+    pub fn dated(&self) -> bool {
+        self.location.is_some() || !self.overrides.is_empty() || self.calendar.is_some()
     }
 
     #[inline]
@@ -149,121 +903,433 @@ impl TimeTrack {
 }
 
 #[inline]
-fn read_config(path: PathBuf) -> Result<Config> {
+pub fn read_config(path: PathBuf) -> Result<Config> {
+    let format = Format::from_path(&path)
+        .ok_or_else(|| eyre::eyre!("Unknown config file format {:?}", path))?;
     let s = std::fs::read_to_string(path.clone())
         .wrap_err_with(|| format!("Failed to read config file {:?}", path))?;
-    ron::from_str(s.as_str())
-        .map_err(|e| {
-            eyre::eyre!(format!(
-                "file {:?}\n{:?} at line:{} col:{}",
-                path,
-                e.code.to_string(),
-                e.position.line,
-                e.position.col
-            ))
-        })
+    format
+        .parse(s.as_str())
+        .map_err(|e| eyre::eyre!(format!("file {:?}\n{}", path, e)))
         .wrap_err("Failed to parse config file")
 }
 
-#[inline]
-fn validate_tracks(mut config: Config) -> Result<Config> {
-    if config.tracks.is_empty() {
+// read config and all the includes it references, depth first.
+// included tracks, templates, themes and palette are appended to the including config, includer's ones win.
+// A file is read once, the later includes of it are skipped, an include of a file being read is a cycle.
+fn read_config_tree(
+    path: PathBuf,
+    visited: &mut Vec<PathBuf>,
+    loaded: &mut BTreeSet<PathBuf>,
+) -> Result<Config> {
+    let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+    if visited.contains(&key) {
         eyre::bail!(
-            "No tracks defined in the config, which implies it's an empty file.
-            \nDefine some tracks, specify another config file, or check the github repo for pre-existing configurations"
+            "Config include cycle\n{:?} is included by itself through {:#?}",
+            path,
+            visited
         );
     }
+    visited.push(key.clone());
+    loaded.insert(key);
+    let mut config = read_config(path.clone())?;
+    config.files.push(path.clone());
+    for (index, track) in config.tracks.iter_mut().enumerate() {
+        track.origin = Origin { file: path.clone(), index };
+    }
+
+    let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    for name in std::mem::take(&mut config.include) {
+        let include = find_config_from(&name, &base)
+            .wrap_err_with(|| format!("Can't find file {:?} included by {:?}", name, path))?;
+        // a file included along several paths is taken once
+        let key = include.canonicalize().unwrap_or_else(|_| include.clone());
+        if loaded.contains(&key) && !visited.contains(&key) {
+            continue;
+        }
+        let included = read_config_tree(include, visited, loaded)?;
+        config.tracks.extend(included.tracks);
+        config.files.extend(included.files);
+        for (name, ranges) in included.templates {
+            config.templates.entry(name).or_insert(ranges);
+        }
+        for (name, theme) in included.themes {
+            config.themes.entry(name).or_insert(theme);
+        }
+        for (name, color) in included.palette {
+            config.palette.entry(name).or_insert(color);
+        }
+        config.overlap = config.overlap.or(included.overlap);
+        for (action, keys) in included.keys {
+            config.keys.entry(action).or_insert(keys);
+        }
+    }
+    visited.pop();
+    Ok(config)
+}
+
+// unknown template names are left with no ranges, see `check::problems`
+#[inline]
+fn resolve_templates(mut config: Config) -> Config {
+    let resolve = |spec: &RangeSpec| match spec {
+        RangeSpec::List(ranges) => ranges.clone(),
+        RangeSpec::Template(name) => config.templates.get(name).cloned().unwrap_or_default(),
+    };
+    let mut tracks = std::mem::take(&mut config.tracks);
+    for track in &mut tracks {
+        track.ranges = resolve(&track.range_spec);
+        for day in &mut track.overrides {
+            day.ranges = resolve(&day.range_spec);
+        }
+    }
+    config.tracks = tracks;
+    config
+}
+
+// Path and events of the track calendar, the file is looked up next to the config file of the track
+pub fn read_calendar(track: &TimeTrack, calendar: &Calendar) -> Result<(PathBuf, Vec<Event>)> {
+    let base = track.origin.file.parent().map(Path::to_path_buf).unwrap_or_default();
+    let path = base.join(&calendar.file);
+    let text = std::fs::read_to_string(&path).wrap_err_with(|| {
+        format!("Failed to read calendar {:?} of track {:?}", path, track.name)
+    })?;
+    // watcher events come with the canonical paths
+    let path = path.canonicalize().unwrap_or(path);
+    let events = ics::parse(&text)
+        .map_err(|e| eyre::eyre!("file {:?}\n{}", path, e))
+        .wrap_err("Failed to parse calendar file")?;
+    Ok((path, events))
+}
+
+// Events of the track calendars and their colors, only the loaded configs have them,
+// `check::run` reports the calendars that fail to read
+fn read_calendars(mut config: Config) -> Result<Config> {
+    let mut tracks = std::mem::take(&mut config.tracks);
+    for track in &mut tracks {
+        let Some(mut calendar) = track.calendar.take() else {
+            continue;
+        };
+        (calendar.path, calendar.events) = read_calendar(track, &calendar)?;
+        calendar.colors =
+            calendar.events.iter().map(|e| calendar.event_color(e, &config)).collect();
+        track.calendar = Some(calendar);
+    }
+    config.tracks = tracks;
+    Ok(config)
+}
+
+// unknown palette names are left as `Reset`, see `check::problems`
+#[inline]
+fn resolve_colors(mut config: Config) -> Config {
+    let mut tracks = std::mem::take(&mut config.tracks);
+    let ranges = tracks.iter_mut().flat_map(|t| {
+        let overrides = t.overrides.iter_mut().flat_map(|o| o.ranges.iter_mut());
+        t.ranges.iter_mut().chain(overrides)
+    });
+    for range in ranges {
+        range.color = config.color(&range.color_spec).unwrap_or(Color::Reset);
+    }
+    config.tracks = tracks;
+    config
+}
+
+// Fails if any of the `check::problems` is an error, otherwise fills in missing titles and sorts ranges
+#[inline]
+#[allow(clippy::unnecessary_sort_by)]
+fn validate_tracks(mut config: Config) -> Result<Config> {
+    let errors: Vec<String> = check::problems(&config)
+        .iter()
+        .filter(|p| p.severity == Severity::Error)
+        .map(|p| p.to_string())
+        .collect();
+    if !errors.is_empty() {
+        eyre::bail!("Invalid config\n{}", errors.join("\n"));
+    }
     for track in &mut config.tracks {
-        match (track.name.len(), track.shortname.len()) {
-            (0, 0) => eyre::bail!(format!(
-                "Unspecified title\n{:#?}\nspecify at least one of the fields ['name', 'shortname'] for this track",
-                track
-            )),
-            (nl, sl) if nl > u16::MAX.into() || sl > u16::MAX.into() => {
-                let msg = format!("Name is way too long\n{:#?}\nrename it to fit at most {} characters, 
-                    although the real limit is `terminal line length` - 13",
-                        track,
-                        u16::MAX);
-                eyre::bail!(msg);
-            },
-            // just clone it to simplify main loop computations
-            (0, _) => track.name = track.shortname.clone(),
-            (_, 0) => track.shortname = track.name.clone(),
-            _ => ()
-        }
-
-        if track.offset.0.abs() > 23 || track.offset.1.abs() > 59 {
-            eyre::bail!(format!(
-                "UTC offset is not within the valid range\n{:#?}\nvalid value ranges for 'offset' is -23..23 for hour and -59..59 for minute",
-                track));
-        }
-
-        if !track.ranges.is_empty() {
-            for range in track.ranges.iter() {
-                let start = range.start;
-                let end = range.end;
-                if start.0 > 23 || end.0 > 24 || start.1 > 59 || end.1 > 59 {
-                    eyre::bail!(format!(
-                "Time is out of range\n{:#?}\nvalid value ranges for 'range.start' and 'range.end' is 0..24 for hour and 0..59 for minute",
-                track));
-                }
-                if start.0 * 100 + start.1 > 2400 {
-                    eyre::bail!(format!("Wrong 'start' time \n{:#?}", track));
-                }
-                if end.0 * 100 + end.1 > 2400 {
-                    eyre::bail!(format!("Wrong 'end' time \n{:#?}", track));
-                }
-                if start.0 * 100 + start.1 >= end.0 * 100 + end.1 {
-                    eyre::bail!(format!("Wrong time ordering\n{:#?}\n'start' and 'end' time should be chronologically ordered such that 'start' << 'end'.", track));
-                }
-            }
-            track
-                .ranges
-                .sort_by(|a, b| (a.start.0 * 100 + a.start.1).cmp(&(b.start.0 * 100 + b.start.1)));
-            for i in 1..track.ranges.len() {
-                let end = track.ranges[i - 1].end;
-                let start = track.ranges[i].start;
-                if start.0 * 100 + start.1 < end.0 * 100 + end.1 {
-                    eyre::bail!(format!(
-                    "Time range overlap\n{:#?}\nRanges should be chronologically exclusive, but these two overlaps \n{:?}\n{:?}",
-                    track,
-                    track.ranges[i - 1],
-                    track.ranges[i]
-                ));
-                }
-            }
+        // just clone it to simplify main loop computations
+        if track.name.is_empty() {
+            track.name = track.shortname.clone();
+        }
+        if track.shortname.is_empty() {
+            track.shortname = track.name.clone();
+        }
+        track
+            .ranges
+            .sort_by(|a, b| (a.start.0 * 100 + a.start.1).cmp(&(b.start.0 * 100 + b.start.1)));
+        for day in &mut track.overrides {
+            day.ranges.sort_by_key(|r| to_minutes(r.start));
         }
     }
     Ok(config)
 }
 
+// Translates config file into the format of the `output` extension.
+// Only the file itself is converted, includes are kept as they are and comments are lost.
+pub fn convert(input: PathBuf, output: &Path, force: bool) -> Result<()> {
+    let format = Format::from_path(output)
+        .ok_or_else(|| eyre::eyre!("Unknown config file format {:?}", output))?;
+    if output.exists() && !force {
+        eyre::bail!("{:?} already exists, use --force to overwrite it", output);
+    }
+    let text = format.to_string(&read_config(input)?).wrap_err("Failed to serialize config")?;
+    std::fs::write(output, text).wrap_err_with(|| format!("Failed to write {:?}", output))
+}
+
+// read config with all the includes and calendars, resolve range templates and palette colors,
+// no validation is done
+pub fn read_configs(path: PathBuf) -> Result<Config> {
+    read_config_tree(path, &mut vec![], &mut BTreeSet::new())
+        .map(resolve_templates)
+        .map(resolve_colors)
+}
+
 pub fn load_config(path: PathBuf) -> Result<Config> {
-    read_config(path).and_then(validate_tracks)
+    read_configs(path).and_then(read_calendars).and_then(validate_tracks)
+}
+
+// <Lin, Win, Mac specific config dir>/mvtime
+pub fn config_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "github 2davy", "mvtime").map(|prj| PathBuf::from(prj.config_dir()))
+}
+
+// directories `find_config` looks for config names in
+pub fn search_dirs() -> Result<Vec<PathBuf>> {
+    let mut dirs = vec![std::env::current_dir()?];
+    dirs.extend(config_dir());
+    Ok(dirs)
+}
+
+// all the config files in the directory sorted by name, empty if there is no such directory
+pub fn list_configs(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file() && Format::from_path(p).is_some())
+        .collect();
+    paths.sort();
+    paths
 }
 
 pub fn find_config(fname: &str) -> Result<PathBuf> {
-    if fname.contains(".ron") {
-        PathBuf::from(fname).canonicalize().wrap_err("failed to locate config file")
+    find_config_from(fname, &std::env::current_dir()?)
+}
+
+// same as `find_config`, with relative paths and names looked up in `base` instead of './'
+pub fn find_config_from(fname: &str, base: &Path) -> Result<PathBuf> {
+    if Format::from_path(Path::new(fname)).is_some() {
+        base.join(fname).canonicalize().wrap_err("failed to locate config file")
     } else {
-        // search for <name>.ron in ./, ~/<CONFIG_DIRS>/mvtime/
+        // search for <name>.<ron|toml|yaml|yml|json> in <base>/, ~/<CONFIG_DIRS>/mvtime/
         let mut paths = vec![];
-        // ./<name>.ron
-        let mut path = std::env::current_dir()?;
-        path.push(fname);
-        path.set_extension("ron");
-        paths.push(path);
-
-        // <Lin, Win, Mac specific config dir>/<name>.ron
-        if let Some(prj) = ProjectDirs::from("com", "github 2davy", "mvtime") {
-            let mut path = PathBuf::from(prj.config_dir());
-            path.push(fname);
-            path.set_extension("ron");
-            paths.push(path);
+        // <base>, <Lin, Win, Mac specific config dir>
+        for dir in std::iter::once(base.to_path_buf()).chain(config_dir()) {
+            for ext in Format::ALL.iter().flat_map(|f| f.extensions()) {
+                let mut path = dir.join(fname);
+                path.set_extension(ext);
+                paths.push(path);
+            }
         }
         paths
             .clone()
             .into_iter()
             .find(|p| p.exists())
-            .ok_or_else(|| eyre::eyre!("{}.ron not found in any of the paths {:#?}", fname, paths))
+            .ok_or_else(|| eyre::eyre!("{} not found in any of the paths {:#?}", fname, paths))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Config> {
+        ron::from_str(s)
+            .map_err(|e| eyre::eyre!(e))
+            .map(resolve_templates)
+            .map(resolve_colors)
+            .and_then(validate_tracks)
+    }
+
+    #[test]
+    fn time_strings() {
+        assert_eq!(parse_time("09:30"), Ok((9, 30)));
+        assert_eq!(parse_time("24:00"), Ok((24, 0)));
+        assert_eq!(parse_time("9:30pm"), Ok((21, 30)));
+        assert_eq!(parse_time("9 PM"), Ok((21, 0)));
+        assert_eq!(parse_time("12am"), Ok((0, 0)));
+        assert_eq!(parse_time("12:15pm"), Ok((12, 15)));
+        for invalid in ["9", "9:3", "13pm", "0am", "a:00", "9:30xm", ""] {
+            assert!(parse_time(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn offset_strings() {
+        assert_eq!(parse_offset("UTC-05:00"), Ok((-5, 0)));
+        assert_eq!(parse_offset("+05:45"), Ok((5, 45)));
+        assert_eq!(parse_offset("-00:30"), Ok((0, -30)));
+        assert_eq!(parse_offset("gmt+3"), Ok((3, 0)));
+        assert_eq!(parse_offset("UTC"), Ok((0, 0)));
+        for invalid in ["UTC-5:3", "+", "5h", "UTC+05:"] {
+            assert!(parse_offset(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(fmt_offset((0, -30)), "UTC-00:30");
+        assert_eq!(fmt_offset((5, 45)), "UTC+05:45");
+    }
+
+    #[test]
+    fn hex_and_palette_colors() {
+        assert_eq!(parse_hex("#fac800"), Ok(Color::Rgb(250, 200, 0)));
+        assert_eq!(parse_hex("#FFF"), Ok(Color::Rgb(255, 255, 255)));
+        for invalid in ["fac800", "#fac80", "#gggggg", "#"] {
+            assert!(parse_hex(invalid).is_err(), "{}", invalid);
+        }
+
+        let config = parse(
+            r##"(palette: {"main": Rgb(250, 200, 0), "pre": Gray},
+                colors: (base: pre),
+                tracks: [(name: "a", shortname: "a", offset: (0, 0),
+                          ranges: [(start: (1, 0), end: (2, 0), color: main),
+                                   (start: (3, 0), end: (4, 0), color: Hex("#00ff00"))])])"##,
+        )
+        .unwrap();
+        let colors: Vec<Color> = config.tracks[0].ranges.iter().map(|r| r.color).collect();
+        assert_eq!(colors, [Color::Rgb(250, 200, 0), Color::Rgb(0, 255, 0)]);
+        assert_eq!(config.theme("dark").unwrap().base, Color::Gray);
+
+        let json: Config = Format::Json
+            .parse(r##"{"palette": {"main": "#fac800"}, "colors": {"title": "main"}}"##)
+            .unwrap();
+        assert_eq!(json.palette["main"], ColorSpec::Color(Color::Rgb(250, 200, 0)));
+        assert_eq!(json.colors.title, Some(ColorSpec::Palette("main".to_string())));
+        assert!(Format::Json.parse::<Config>(r##"{"palette": {"main": "#fac8"}}"##).is_err());
+    }
+
+    #[test]
+    fn offset_and_time_forms_agree() {
+        let config = parse(
+            r#"(tracks: [
+                (name: "a", shortname: "a", offset: (-5, 30)),
+                (name: "b", shortname: "b", offset: "UTC-05:30"),
+                (name: "c", shortname: "c", offset: "-00:30",
+                 ranges: [(start: "9:30am", end: (17, 0), color: White)]),
+                (name: "d", shortname: "d", offset: (0, -30))])"#,
+        )
+        .unwrap();
+        let minutes: Vec<i64> = config.tracks.iter().map(|t| t.offset().num_minutes()).collect();
+        assert_eq!(minutes, vec![-330, -330, -30, -30]);
+        assert_eq!(config.tracks[2].ranges[0].start, (9, 30));
+    }
+
+    #[test]
+    fn template_reference_resolved() {
+        let config = parse(
+            r#"(templates: {"day": [(start:(8,0), end:(18,0), color:White)]},
+                tracks: [(name: "a", shortname: "a", offset: (0, 0), ranges: "day"),
+                         (name: "b", shortname: "b", offset: (0, 0), ranges: [])])"#,
+        )
+        .unwrap();
+        assert_eq!(config.tracks[0].ranges.len(), 1);
+        assert_eq!(config.tracks[0].ranges[0].start, (8, 0));
+        assert!(config.tracks[1].ranges.is_empty());
+    }
+
+    #[test]
+    fn unknown_template_fails() {
+        let config =
+            parse(r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0), ranges: "x")])"#);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn convert_round_trip() {
+        let dir = std::env::temp_dir().join(format!("mvtime-convert-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("main.ron"),
+            r##"(templates: {"day": [(start:(8,0), end:(18,0), color:Rgb(1, 2, 3), fill: true)]},
+                palette: {"main": Hex("#fac800")},
+                tracks: [(name: "a", shortname: "a", offset: (-3, -30), ranges: "day"),
+                         (name: "b", shortname: "b", offset: (1, 0),
+                          ranges: [(start:(1,0), end:(2,0), color:Yellow),
+                                   (start:(3,0), end:(4,0), color:main)])])"##,
+        )
+        .unwrap();
+        let mut converted = vec![];
+        for ext in ["toml", "yaml", "json", "ron"] {
+            let path = dir.join("out").with_extension(ext);
+            convert(dir.join("main.ron"), &path, false).unwrap();
+            converted.push(load_config(path.clone()));
+            assert!(convert(dir.join("main.ron"), &path, false).is_err());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for config in converted {
+            let config = config.unwrap();
+            assert_eq!(config.tracks.len(), 2);
+            assert_eq!(config.tracks[0].offset, (-3, -30));
+            assert_eq!(config.tracks[0].ranges[0].color, Color::Rgb(1, 2, 3));
+            assert_eq!(config.tracks[0].ranges[0].fill, Some(true));
+            assert_eq!(config.tracks[1].ranges[0].color, Color::Yellow);
+            assert_eq!(config.tracks[1].ranges[0].fill, None);
+            assert_eq!(config.tracks[1].ranges[1].color, Color::Rgb(250, 200, 0));
+        }
+    }
+
+    #[test]
+    fn list_configs_known_formats_only() {
+        let dir = std::env::temp_dir().join(format!("mvtime-list-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested.ron")).unwrap();
+        for name in ["b.ron", "a.toml", "notes.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let configs = list_configs(&dir);
+        let missing = list_configs(&dir.join("missing"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(configs, vec![dir.join("a.toml"), dir.join("b.ron")]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn includes_merged() {
+        let dir = std::env::temp_dir().join(format!("mvtime-include-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("common.ron"),
+            r#"(templates: {"day": [(start:(8,0), end:(18,0), color:White)]},
+                tracks: [(name: "b", shortname: "b", offset: (1, 0), ranges: "day")])"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("main.ron"),
+            r#"(include: ["common"], tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        )
+        .unwrap();
+        let config = load_config(dir.join("main.ron"));
+        // both include the same file, its tracks are taken once
+        std::fs::write(dir.join("left.ron"), r#"(include: ["common"])"#).unwrap();
+        std::fs::write(dir.join("right.ron"), r#"(include: ["common.ron"])"#).unwrap();
+        std::fs::write(
+            dir.join("diamond.ron"),
+            r#"(include: ["left", "right"], tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        )
+        .unwrap();
+        let diamond = load_config(dir.join("diamond.ron"));
+        let cycle = {
+            std::fs::write(dir.join("common.ron"), r#"(include: ["main.ron"])"#).unwrap();
+            load_config(dir.join("main.ron"))
+        };
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.tracks.len(), 2);
+        assert_eq!(config.tracks[1].ranges.len(), 1);
+        assert_eq!(config.files.len(), 2);
+        let diamond = diamond.unwrap();
+        assert_eq!(diamond.tracks.len(), 2);
+        assert_eq!(diamond.files.len(), 4);
+        assert!(cycle.is_err());
     }
 }
@@ -0,0 +1,173 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{cell::Cell, fmt, path::Path};
+
+// Config file formats, picked by the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ron,
+    Toml,
+    Yaml,
+    Json,
+}
+
+// parse error message with the 1-based line and column, if the parser reports one
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub position: Option<(usize, usize)>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some((line, col)) => write!(f, "{:?} at line:{} col:{}", self.message, line, col),
+            None => write!(f, "{:?}", self.message),
+        }
+    }
+}
+
+thread_local! {
+    // RON has no strings in place of enums, palette names are written as 'Palette("main")' there
+    static WRITING_RON: Cell<bool> = const { Cell::new(false) };
+}
+
+#[inline]
+pub fn writing_ron() -> bool {
+    WRITING_RON.with(Cell::get)
+}
+
+impl Format {
+    // in the order configs are searched for by name
+    pub const ALL: [Format; 4] = [Format::Ron, Format::Toml, Format::Yaml, Format::Json];
+
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Format::Ron => &["ron"],
+            Format::Toml => &["toml"],
+            Format::Yaml => &["yaml", "yml"],
+            Format::Json => &["json"],
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        Format::ALL.into_iter().find(|f| f.extensions().contains(&ext.as_str()))
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, s: &str) -> Result<T, ParseError> {
+        match self {
+            Format::Ron => ron::from_str(s).map_err(|e| ParseError {
+                message: e.code.to_string(),
+                position: Some((e.position.line, e.position.col)),
+            }),
+            Format::Toml => toml::from_str(s).map_err(|e| ParseError {
+                message: e.message().to_string(),
+                position: e.span().map(|span| line_col(s, span.start)),
+            }),
+            Format::Yaml => serde_yaml::from_str(s).map_err(|e| {
+                let position = e.location().map(|l| (l.line(), l.column()));
+                ParseError { message: strip_position(e.to_string(), position), position }
+            }),
+            Format::Json => serde_json::from_str(s).map_err(|e| {
+                let position = Some((e.line(), e.column()));
+                ParseError { message: strip_position(e.to_string(), position), position }
+            }),
+        }
+    }
+
+    pub fn to_string<T: Serialize>(self, value: &T) -> eyre::Result<String> {
+        Ok(match self {
+            Format::Ron => {
+                let config = ron::ser::PrettyConfig::default().struct_names(false);
+                WRITING_RON.with(|ron| ron.set(true));
+                let text = ron::ser::to_string_pretty(value, config);
+                WRITING_RON.with(|ron| ron.set(false));
+                text?
+            }
+            Format::Toml => toml::to_string(value)?,
+            Format::Yaml => serde_yaml::to_string(value)?,
+            Format::Json => serde_json::to_string_pretty(value)?,
+        })
+    }
+}
+
+// yaml and json errors have the position appended to the message
+#[inline]
+fn strip_position(message: String, position: Option<(usize, usize)>) -> String {
+    match position {
+        Some((line, col)) => {
+            let suffix = format!(" at line {} column {}", line, col);
+            message.strip_suffix(&suffix).map(String::from).unwrap_or(message)
+        }
+        None => message,
+    }
+}
+
+// 1-based line and column of the byte offset
+#[inline]
+pub fn line_col(s: &str, offset: usize) -> (usize, usize) {
+    let before = &s[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn format_by_extension() {
+        assert_eq!(Format::from_path(Path::new("a/b.ron")), Some(Format::Ron));
+        assert_eq!(Format::from_path(Path::new("b.YML")), Some(Format::Yaml));
+        assert_eq!(Format::from_path(Path::new("b.json")), Some(Format::Json));
+        assert_eq!(Format::from_path(Path::new("b.txt")), None);
+        assert_eq!(Format::from_path(Path::new("toml")), None);
+    }
+
+    #[test]
+    fn error_positions() {
+        for (format, src, position) in [
+            (Format::Ron, "{\"a\": 1,\n \"b\": x}", (2, 7)),
+            (Format::Json, "{\"a\": 1,\n \"b\": x}", (2, 7)),
+            (Format::Toml, "a = 1\nb = x", (2, 5)),
+            (Format::Yaml, "a: 1\nb: [x", (2, 4)),
+        ] {
+            let e = format.parse::<std::collections::HashMap<String, u8>>(src).unwrap_err();
+            assert_eq!(e.position, Some(position), "{:?}: {}", format, e.message);
+            assert!(!e.message.contains("line"), "{:?}: {}", format, e.message);
+        }
+    }
+
+    #[test]
+    fn ron_palette_names() {
+        let config: crate::config::Config = Format::Ron
+            .parse(
+                r##"(palette: {"main": Hex("#fac800"), "alt": Red},
+                    tracks: [(name: "a", shortname: "a", offset: (0, 0), ranges: [
+                        (start: (0, 0), end: (1, 0), color: main),
+                        (start: (1, 0), end: (2, 0), color: Palette("alt"))])])"##,
+            )
+            .unwrap();
+        let crate::config::RangeSpec::List(ranges) = &config.tracks[0].range_spec else {
+            panic!("not a list");
+        };
+        let colors: Vec<String> = ranges.iter().map(|r| format!("{:?}", r.color_spec)).collect();
+        assert_eq!(colors, ["Palette(\"main\")", "Palette(\"alt\")"]);
+        // palette names are strings elsewhere, and read back from RON
+        let ron = Format::Ron.to_string(&config).unwrap();
+        assert!(ron.contains(r#"color: Palette("main")"#), "{}", ron);
+        assert!(!writing_ron());
+        let json = Format::Json.to_string(&config).unwrap();
+        assert!(json.contains(r#""color": "main""#), "{}", json);
+        let back: crate::config::Config = Format::Ron.parse(&ron).unwrap();
+        let crate::config::RangeSpec::List(back) = &back.tracks[0].range_spec else {
+            panic!("not a list");
+        };
+        assert_eq!(back[0].color_spec, ranges[0].color_spec);
+        // a string in place of a color is still an error in RON
+        let e = Format::Ron.parse::<crate::config::Config>("(palette: {\"a\": \"#fac800\"})");
+        assert_eq!(e.unwrap_err().position, Some((1, 17)));
+    }
+}
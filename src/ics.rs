@@ -0,0 +1,668 @@
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::MINUTES_PER_DAY;
+
+// longest event, longer ones are likely broken and would only slow the lookups down
+const MAX_DAYS: i64 = 366;
+
+// Time zone an event time is written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    // zone of the TZID parameter
+    Named(Tz),
+    // floating times and dates are in the track time zone
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Moment {
+    time: NaiveDateTime,
+    zone: Zone,
+}
+
+impl Moment {
+    // UTC of the moment for a track with the `offset`
+    fn utc(&self, offset: Duration) -> NaiveDateTime {
+        match self.zone {
+            Zone::Utc => self.time,
+            Zone::Named(tz) => match tz.from_local_datetime(&self.time).earliest() {
+                Some(time) => time.naive_utc(),
+                // skipped by a DST change, the offset before the change is taken
+                None => {
+                    let offset = tz.offset_from_utc_datetime(&self.time).fix();
+                    self.time - Duration::seconds(offset.local_minus_utc().into())
+                }
+            },
+            Zone::Local => self.time - offset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Moment>,
+    // days of a weekly rule, the start day if empty
+    weekdays: Vec<Weekday>,
+}
+
+// VEVENT of a calendar
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub summary: String,
+    pub categories: Vec<String>,
+    start: Moment,
+    length: Duration,
+    // recurrence, other than daily and weekly ones are taken as a single event
+    rule: Option<Rule>,
+    // EXDATE and the starts of the instances changed by RECURRENCE-ID events
+    exceptions: Vec<Moment>,
+}
+
+impl Event {
+    // Starts of the occurrences with the start date within `from..=to`, in the event zone
+    fn starts(&self, from: NaiveDate, to: NaiveDate, offset: Duration) -> Vec<Moment> {
+        let Some(rule) = &self.rule else {
+            return match self.start.time.date() {
+                date if (from..=to).contains(&date) => vec![self.start],
+                _ => vec![],
+            };
+        };
+        let first = self.start.time.date();
+        let interval = i64::from(rule.interval.max(1));
+        let days = |n: i64| Duration::try_days(n).unwrap_or(Duration::MAX);
+        let after = |date: NaiveDate, n: i64| date.checked_add_signed(days(n));
+        // dates of the rule up to `to`, starting at the period with `from`, and the number
+        // of the occurrences before them
+        let mut dates = vec![];
+        let mut n = match rule.frequency {
+            Frequency::Daily => {
+                let skipped = ((from - first).num_days().max(0) + interval - 1) / interval;
+                let mut date = after(first, skipped * interval);
+                while let Some(day) = date.filter(|day| *day <= to) {
+                    dates.push(day);
+                    date = after(day, interval);
+                }
+                skipped
+            }
+            Frequency::Weekly => {
+                let mut weekdays = match rule.weekdays[..] {
+                    [] => vec![first.weekday()],
+                    ref weekdays => weekdays.to_vec(),
+                };
+                weekdays.sort_by_key(|day| day.num_days_from_monday());
+                weekdays.dedup();
+                let offsets: Vec<i64> =
+                    weekdays.iter().map(|day| day.num_days_from_monday().into()).collect();
+                let monday = first - days(first.weekday().num_days_from_monday().into());
+                let weeks = (from - monday).num_days().max(0) / 7 / interval * interval;
+                let in_first = offsets.iter().filter(|d| after(monday, **d) >= Some(first)).count();
+                let skipped = match weeks {
+                    0 => 0,
+                    _ => in_first as i64 + (weeks / interval - 1) * offsets.len() as i64,
+                };
+                let mut week = after(monday, weeks * 7);
+                while let Some(start) = week.filter(|start| *start <= to) {
+                    let week_dates = offsets.iter().filter_map(|d| after(start, *d));
+                    dates.extend(week_dates.filter(|date| (first..=to).contains(date)));
+                    week = after(start, interval * 7);
+                }
+                skipped
+            }
+        };
+        let mut starts = vec![];
+        for date in dates {
+            n += 1;
+            let start = Moment { time: date.and_time(self.start.time.time()), ..self.start };
+            let over = rule.until.is_some_and(|until| start.utc(offset) > until.utc(offset));
+            if over || rule.count.is_some_and(|count| n > count.into()) {
+                break;
+            }
+            let excluded = self.exceptions.iter().any(|e| e.utc(offset) == start.utc(offset));
+            if date >= from && !excluded {
+                starts.push(start);
+            }
+        }
+        starts
+    }
+}
+
+// Spans of the events on the local `date` of a track with the UTC `offset`, as minutes since the local
+// midnight along with the event index. Events starting later are on top of the overlapping ones,
+// so a meeting shows up within an all-day shift.
+pub fn day_spans(events: &[Event], date: NaiveDate, offset: Duration) -> Vec<(u16, u16, usize)> {
+    let day_start = date.and_time(NaiveTime::MIN) - offset;
+    let day_end = day_start + Duration::days(1);
+    let mut spans = vec![];
+    for (i, event) in events.iter().enumerate() {
+        // occurrences of the neighbour days might reach the local day
+        let days = Duration::try_days(event.length.num_days() + 2).unwrap_or(Duration::MAX);
+        let from = date.checked_sub_signed(days).unwrap_or(NaiveDate::MIN);
+        let to = date.checked_add_signed(Duration::days(2)).unwrap_or(NaiveDate::MAX);
+        for start in event.starts(from, to, offset) {
+            let start = start.utc(offset);
+            let (start, end) = (start.max(day_start), (start + event.length).min(day_end));
+            if start < end {
+                let minutes = |time: NaiveDateTime| (time - day_start).num_minutes() as u16;
+                spans.push((minutes(start), minutes(end), i));
+            }
+        }
+    }
+    let mut runs: Vec<(u16, u16, usize)> = vec![];
+    for minute in 0..MINUTES_PER_DAY {
+        let top = spans
+            .iter()
+            .filter(|(start, end, _)| (*start..*end).contains(&minute))
+            .max_by_key(|(start, end, _)| (*start, std::cmp::Reverse(*end)));
+        match (runs.last_mut(), top) {
+            (Some((_, end, last)), Some((_, _, i))) if *end == minute && last == i => *end += 1,
+            (_, Some((_, _, i))) => runs.push((minute, minute + 1, *i)),
+            _ => {}
+        }
+    }
+    runs
+}
+
+// Unfolded content lines with the number of their first line
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = vec![];
+    for (n, line) in text.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push((n + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+type Params = Vec<(String, String)>;
+
+// "NAME;PARAM=value;PARAM=\"quoted:value\":value" => (NAME, [(PARAM, value)], value)
+fn property(line: &str) -> Option<(String, Params, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.trim().to_uppercase();
+    let params = head
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+fn param<'a>(params: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+// "20261127", "20261127T093000Z", "20261127T093000" with an optional TZID, true for dates
+fn moment(value: &str, params: &[(String, String)]) -> Result<(Moment, bool), String> {
+    let value = value.trim();
+    let err = || format!("invalid date {:?}, expected 'YYYYMMDD' or 'YYYYMMDDTHHMMSS[Z]'", value);
+    if param(params, "VALUE") == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| err())?;
+        if !(1..=9999).contains(&date.year()) {
+            return Err(err());
+        }
+        return Ok((Moment { time: date.and_time(NaiveTime::MIN), zone: Zone::Local }, true));
+    }
+    let (local, zone) = match (value.strip_suffix('Z'), param(params, "TZID")) {
+        (Some(utc), _) => (utc, Zone::Utc),
+        // zones unknown to the tz database, e.g. Windows ones, are taken as the track one
+        (None, Some(tzid)) => (value, tzid.parse().map_or(Zone::Local, Zone::Named)),
+        (None, None) => (value, Zone::Local),
+    };
+    let time = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").map_err(|_| err())?;
+    if !(1..=9999).contains(&time.year()) {
+        return Err(err());
+    }
+    Ok((Moment { time, zone }, false))
+}
+
+// "PT1H30M", "P1D", "P2W", up to `MAX_DAYS`
+fn duration(value: &str) -> Result<Duration, String> {
+    let err = || format!("invalid duration {:?}, expected e.g. 'PT1H30M' or 'P1D'", value);
+    let long = || format!("duration {:?} is longer than {} days", value, MAX_DAYS);
+    let rest = value.trim().trim_start_matches('+');
+    let rest = rest.strip_prefix('P').ok_or_else(err)?;
+    let (mut total, mut number, mut time) = (Duration::zero(), String::new(), false);
+    for c in rest.chars() {
+        let n = || match number.is_empty() {
+            true => Err(err()),
+            false => number.parse::<i64>().map_err(|_| long()),
+        };
+        let part = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'T' => {
+                time = true;
+                Some(Duration::zero())
+            }
+            'W' if !time => Duration::try_weeks(n()?),
+            'D' if !time => Duration::try_days(n()?),
+            'H' if time => Duration::try_hours(n()?),
+            'M' if time => Duration::try_minutes(n()?),
+            'S' if time => Duration::try_seconds(n()?),
+            _ => return Err(err()),
+        };
+        total = part.and_then(|part| total.checked_add(&part)).ok_or_else(long)?;
+        number.clear();
+    }
+    match number.is_empty() {
+        true if total > Duration::days(MAX_DAYS) => Err(long()),
+        true => Ok(total),
+        false => Err(err()),
+    }
+}
+
+// "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20261231T000000Z", None for the unsupported frequencies
+fn rule(value: &str) -> Result<Option<Rule>, String> {
+    let err = |part: &str| format!("invalid recurrence rule part {:?}", part);
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        weekdays: vec![],
+    };
+    for part in value.trim().split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=').ok_or_else(|| err(part))?;
+        match key.to_uppercase().as_str() {
+            "FREQ" => match value.to_uppercase().as_str() {
+                "DAILY" => rule.frequency = Frequency::Daily,
+                "WEEKLY" => rule.frequency = Frequency::Weekly,
+                _ => return Ok(None),
+            },
+            "INTERVAL" => rule.interval = value.parse().map_err(|_| err(part))?,
+            "COUNT" => rule.count = Some(value.parse().map_err(|_| err(part))?),
+            "UNTIL" => rule.until = Some(moment(value, &[])?.0),
+            "BYDAY" => {
+                for day in value.split(',') {
+                    // "1MO" like days of the monthly rules lose the number
+                    let day = day
+                        .trim_start_matches(|c: char| c.is_ascii_digit() || c == '+' || c == '-');
+                    rule.weekdays.push(match day.to_uppercase().as_str() {
+                        "MO" => Weekday::Mon,
+                        "TU" => Weekday::Tue,
+                        "WE" => Weekday::Wed,
+                        "TH" => Weekday::Thu,
+                        "FR" => Weekday::Fri,
+                        "SA" => Weekday::Sat,
+                        "SU" => Weekday::Sun,
+                        _ => return Err(err(part)),
+                    });
+                }
+            }
+            // WKST and the rest of the parts don't change daily and weekly rules much
+            _ => {}
+        }
+    }
+    Ok(Some(rule))
+}
+
+// TEXT values, split at the unescaped commas
+fn text_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, items.last_mut()) {
+            ('\\', Some(item)) => match chars.next() {
+                Some('n' | 'N') => item.push('\n'),
+                Some(c) => item.push(c),
+                None => {}
+            },
+            (',', _) => items.push(String::new()),
+            (c, Some(item)) => item.push(c),
+            (_, None) => {}
+        }
+    }
+    items
+}
+
+// properties of a VEVENT being parsed
+#[derive(Default)]
+struct Draft {
+    // line of the BEGIN
+    line: usize,
+    uid: String,
+    summary: String,
+    categories: Vec<String>,
+    start: Option<(Moment, bool)>,
+    end: Option<Moment>,
+    length: Option<Duration>,
+    rule: Option<Rule>,
+    exceptions: Vec<Moment>,
+    recurrence_id: Option<Moment>,
+    cancelled: bool,
+}
+
+// Events of an iCalendar text, cancelled events are left out
+pub fn parse(text: &str) -> Result<Vec<Event>, String> {
+    let mut components: Vec<String> = vec![];
+    let mut drafts: Vec<Draft> = vec![];
+    for (n, line) in unfold(text) {
+        let at = |e: String| format!("line {}: {}", n, e);
+        let Some((name, params, value)) = property(&line) else {
+            return Err(at(format!("invalid content line {:?}", line)));
+        };
+        match name.as_str() {
+            "BEGIN" => {
+                components.push(value.trim().to_uppercase());
+                if value.trim().eq_ignore_ascii_case("VEVENT") {
+                    drafts.push(Draft { line: n, ..Draft::default() });
+                }
+                continue;
+            }
+            "END" => {
+                components.pop();
+                continue;
+            }
+            _ => {}
+        }
+        // properties of VALARM and the like nested in events are not the event ones
+        let (Some("VEVENT"), Some(draft)) =
+            (components.last().map(String::as_str), drafts.last_mut())
+        else {
+            continue;
+        };
+        match name.as_str() {
+            "UID" => draft.uid = value.to_string(),
+            "SUMMARY" => draft.summary = text_list(value).join(","),
+            "CATEGORIES" => draft.categories.extend(text_list(value)),
+            "STATUS" => draft.cancelled = value.trim().eq_ignore_ascii_case("CANCELLED"),
+            "DTSTART" => draft.start = Some(moment(value, &params).map_err(at)?),
+            "DTEND" => draft.end = Some(moment(value, &params).map_err(at)?.0),
+            "DURATION" => draft.length = Some(duration(value).map_err(at)?),
+            "RRULE" => draft.rule = rule(value).map_err(at)?,
+            "RECURRENCE-ID" => draft.recurrence_id = Some(moment(value, &params).map_err(at)?.0),
+            "EXDATE" => {
+                for value in value.split(',') {
+                    draft.exceptions.push(moment(value, &params).map_err(at)?.0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // changed instances of the recurring events replace the original ones
+    let changed: Vec<(String, Moment)> =
+        drafts.iter().filter_map(|draft| Some((draft.uid.clone(), draft.recurrence_id?))).collect();
+    let mut events = vec![];
+    for mut draft in drafts {
+        let Some((start, date)) = draft.start else {
+            continue;
+        };
+        if draft.recurrence_id.is_none() {
+            let instances = changed.iter().filter(|(uid, _)| *uid == draft.uid);
+            draft.exceptions.extend(instances.map(|(_, start)| *start));
+        }
+        let length = match (draft.length, draft.end) {
+            (Some(length), _) => length,
+            (None, Some(end)) => end.utc(Duration::zero()) - start.utc(Duration::zero()),
+            // a date lasts the whole day, a time is just a point in time
+            (None, None) if date => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+        if length > Duration::days(MAX_DAYS) {
+            let days = length.num_days();
+            return Err(format!(
+                "line {}: event lasts {} days, longer than {}",
+                draft.line, days, MAX_DAYS
+            ));
+        }
+        if draft.cancelled || length <= Duration::zero() {
+            continue;
+        }
+        events.push(Event {
+            summary: draft.summary,
+            categories: draft.categories,
+            start,
+            length,
+            rule: if draft.recurrence_id.is_some() { None } else { draft.rule },
+            exceptions: draft.exceptions,
+        });
+    }
+    Ok(events)
+}
+
+// VEVENT of an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    // same for the same range and day across the exports, so subscribed clients update the events
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+    pub description: String,
+    pub categories: Vec<String>,
+}
+
+// TEXT value with the special characters escaped
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// content line folded at 75 octets, continuation lines start with a space
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+// iCalendar text of the entries, times are in UTC
+pub fn to_string(entries: &[Entry], stamp: DateTime<Utc>) -> String {
+    let utc = |time: &DateTime<Utc>| time.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//mvtime//EN", "CALSCALE:GREGORIAN"] {
+        fold(line, &mut out);
+    }
+    for entry in entries {
+        fold("BEGIN:VEVENT", &mut out);
+        fold(&format!("UID:{}", entry.uid), &mut out);
+        fold(&format!("DTSTAMP:{}", utc(&stamp)), &mut out);
+        fold(&format!("DTSTART:{}", utc(&entry.start)), &mut out);
+        fold(&format!("DTEND:{}", utc(&entry.end)), &mut out);
+        fold(&format!("SUMMARY:{}", escape(&entry.summary)), &mut out);
+        if !entry.description.is_empty() {
+            fold(&format!("DESCRIPTION:{}", escape(&entry.description)), &mut out);
+        }
+        if !entry.categories.is_empty() {
+            let categories: Vec<String> = entry.categories.iter().map(|c| escape(c)).collect();
+            fold(&format!("CATEGORIES:{}", categories.join(",")), &mut out);
+        }
+        fold("TRANSP:TRANSPARENT", &mut out);
+        fold("END:VEVENT", &mut out);
+    }
+    fold("END:VCALENDAR", &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:standup\r
+SUMMARY:Standup\r
+CATEGORIES:MEETING,TEAM\r
+DTSTART;TZID=Europe/London:20261102T093000\r
+DURATION:PT15M\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=5\r
+EXDATE;TZID=Europe/London:20261104T093000\r
+BEGIN:VALARM\r
+TRIGGER:-PT5M\r
+DURATION:PT1H\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup\r
+RECURRENCE-ID;TZID=Europe/London:20261106T093000\r
+SUMMARY:Standup\r
+DTSTART;TZID=Europe/London:20261106T100000\r
+DTEND;TZID=Europe/London:20261106T101500\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:on-call\r
+SUMMARY:On-call\\, primary\r
+DTSTART;VALUE=DATE:20261109\r
+DTEND;VALUE=DATE:20261111\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:release\r
+SUMMARY:Release\r
+DTSTART:20261110T220000Z\r
+DTEND:20261111T020000Z\r
+STATUS:CONFIRMED\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn spans(events: &[Event], day: u32, offset: i64) -> Vec<(u16, u16, usize)> {
+        let date = NaiveDate::from_ymd_opt(2026, 11, day).unwrap();
+        day_spans(events, date, Duration::hours(offset))
+    }
+
+    #[test]
+    fn events_parsed() {
+        let events = parse(CALENDAR).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].categories, ["MEETING", "TEAM"]);
+        assert_eq!(events[0].length, Duration::minutes(15));
+        assert_eq!(events[0].exceptions.len(), 2);
+        assert_eq!(events[2].summary, "On-call, primary");
+        assert_eq!(events[2].length, Duration::days(2));
+        assert!(parse("BEGIN:VEVENT\nDTSTART:2026-11-09\nEND:VEVENT")
+            .unwrap_err()
+            .starts_with("line 2"));
+        assert_eq!(duration("P1DT2H"), Ok(Duration::hours(26)));
+        assert!(duration("PT1D").is_err());
+    }
+
+    #[test]
+    fn recurring_events_of_a_day() {
+        let events = parse(CALENDAR).unwrap();
+        // London is UTC+0 in November, New York is UTC-5
+        assert_eq!(spans(&events, 2, 0), [(570, 585, 0)]);
+        assert_eq!(spans(&events, 2, -5), [(270, 285, 0)]);
+        // excluded, moved to 10:00, and the last of the five
+        assert_eq!(spans(&events, 4, 0), []);
+        assert_eq!(spans(&events, 6, 0), [(600, 615, 1)]);
+        assert_eq!(spans(&events, 11, 0), [(0, 120, 3), (570, 585, 0)]);
+        assert_eq!(spans(&events, 13, 0), []);
+        // the all-day event is in the track zone, later events are on top of it
+        assert_eq!(spans(&events, 9, 0), [(0, 570, 2), (570, 585, 0), (585, 1440, 2)]);
+        assert_eq!(spans(&events, 10, 0), [(0, 1320, 2), (1320, 1440, 3)]);
+        assert_eq!(spans(&events, 10, 3), [(0, 1440, 2)]);
+        assert_eq!(spans(&events, 11, -3), [(390, 405, 0)]);
+    }
+
+    #[test]
+    fn broken_lengths_and_old_rules() {
+        let event = |lines: &str| parse(&format!("BEGIN:VEVENT\n{}\nEND:VEVENT", lines));
+        let err = event("DTSTART:20261109T090000Z\nDURATION:P99999999999999W").unwrap_err();
+        assert!(err.starts_with("line 3: duration"), "{}", err);
+        assert!(event("DTSTART:20261109T090000Z\nDURATION:P1000000000D").is_err());
+        let err = event("DTSTART:20261109T090000Z\nDTEND:99991231T000000Z").unwrap_err();
+        assert!(err.starts_with("line 1: event lasts"), "{}", err);
+        assert!(event("DTSTART:+1000001109T090000Z").is_err());
+        assert_eq!(duration(&format!("P{}D", MAX_DAYS)), Ok(Duration::days(MAX_DAYS)));
+
+        // every other Monday and Wednesday from a Wednesday, the occurrences are counted from the start
+        let events = event(
+            "DTSTART:19000103T090000Z\nDTEND:19000103T100000Z\nRRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE",
+        )
+        .unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let starts = |events: &[Event], from, to| -> Vec<NaiveDate> {
+            events[0].starts(from, to, Duration::zero()).iter().map(|s| s.time.date()).collect()
+        };
+        assert_eq!(
+            starts(&events, date(1900, 1, 1), date(1900, 1, 20)),
+            [date(1900, 1, 3), date(1900, 1, 15), date(1900, 1, 17)]
+        );
+        assert_eq!(
+            starts(&events, date(1900, 1, 16), date(1900, 2, 1)),
+            [date(1900, 1, 17), date(1900, 1, 29), date(1900, 1, 31)]
+        );
+        // 2026-11-09 is in an odd week since then, 2026-11-16 in an even one
+        assert_eq!(day_spans(&events, date(2026, 11, 9), Duration::zero()), []);
+        assert_eq!(day_spans(&events, date(2026, 11, 16), Duration::zero()), [(540, 600, 0)]);
+        // the fourth occurrence is the last one
+        let counted = event("DTSTART:19000103T090000Z\nDTEND:19000103T100000Z\nRRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=4").unwrap();
+        assert_eq!(
+            starts(&counted, date(1900, 1, 16), date(1900, 2, 28)),
+            [date(1900, 1, 17), date(1900, 1, 29)]
+        );
+        let daily = event(
+            "DTSTART:19000101T090000Z\nDTEND:19000101T100000Z\nRRULE:FREQ=DAILY;INTERVAL=3;COUNT=4",
+        )
+        .unwrap();
+        assert_eq!(
+            starts(&daily, date(1900, 1, 5), date(1900, 1, 30)),
+            [date(1900, 1, 7), date(1900, 1, 10)]
+        );
+    }
+
+    #[test]
+    fn entries_written_and_read_back() {
+        let start = Utc.with_ymd_and_hms(2026, 11, 27, 14, 30, 0).unwrap();
+        let entry = Entry {
+            uid: "nyse-20261127-0930@mvtime".to_string(),
+            start,
+            end: start + Duration::hours(3) + Duration::minutes(30),
+            summary: "NYSE".to_string(),
+            description: "Early close; 09:30-13:00, New York".repeat(3),
+            categories: vec!["main".to_string()],
+        };
+        let text = to_string(&[entry], start);
+        assert!(text.lines().all(|line| line.len() <= 76), "{}", text);
+        assert!(text.contains("DTSTART:20261127T143000Z\r\nDTEND:20261127T180000Z\r\n"));
+        let events = parse(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "NYSE");
+        assert_eq!(events[0].categories, ["main"]);
+        assert_eq!(spans(&events, 27, -5), [(570, 780, 0)]);
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetName, Tz};
+use eyre::{Result, WrapErr};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::config;
+
+// Zone track of the starter config
+pub struct Zone {
+    pub name: String,
+    pub shortname: String,
+    pub offset: (i16, i16),
+    pub local: bool,
+}
+
+impl Zone {
+    // zone's UTC offset at the given moment, so DST is taken into account as of now
+    pub fn new(tz: Tz, now: DateTime<Utc>, local: bool) -> Self {
+        let offset = tz.offset_from_utc_datetime(&now.naive_utc());
+        let seconds = offset.fix().local_minus_utc();
+        let city = tz.name().rsplit('/').next().unwrap_or_else(|| tz.name()).replace('_', " ");
+        Self {
+            name: if local { format!("{} (local)", city) } else { city },
+            shortname: offset.abbreviation().to_string(),
+            offset: ((seconds / 3600) as i16, (seconds % 3600 / 60) as i16),
+            local,
+        }
+    }
+}
+
+// system time zone, if it's known to the tz database
+pub fn local_zone() -> Option<Tz> {
+    iana_time_zone::get_timezone().ok().and_then(|name| name.parse().ok())
+}
+
+pub fn parse_zone(name: &str) -> Result<Tz> {
+    name.parse().map_err(|e| eyre::eyre!("Unknown time zone {:?}: {}", name, e))
+}
+
+// '<dir>/<name>.ron', the name is a plain file name, its extension is replaced as the config is RON
+pub fn config_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || name == "."
+        || name.contains("..")
+        || name.contains(std::path::is_separator)
+    {
+        eyre::bail!("Invalid config name {:?}, expected a file name without a directory", name);
+    }
+    Ok(dir.join(name).with_extension("ron"))
+}
+
+// Starter config text, every zone gets a working hours template,
+// the local one shows up with a badge and seconds.
+pub fn starter_config(zones: &[Zone], now: DateTime<Utc>) -> String {
+    let mut s = String::new();
+    let _ = writeln!(s, "// generated by `mvtime init` on {}", now.format("%Y-%m-%d"));
+    let _ = writeln!(s, "// offsets are fixed, so update them when daylight saving time changes");
+    s.push_str(
+        r#"(
+templates: {
+    "workday": [
+        (start:(7, 0), end:(9, 0), color: Yellow),
+        (start:(9, 0), end:(18, 0), color: White),
+        (start:(18, 0), end:(22, 0), color: Blue),
+    ],
+},
+tracks: [
+"#,
+    );
+    for zone in zones {
+        let label = if zone.local {
+            "(blink: true, seconds: true, fill: true, use_range_color: true)"
+        } else {
+            "(use_range_color: true)"
+        };
+        let _ = write!(
+            s,
+            r#"(
+	name: {:?},
+	shortname: {:?},
+	offset: {:?},
+	show_badge: {},
+	time_label: {},
+	ranges: "workday"
+),
+"#,
+            zone.name,
+            zone.shortname,
+            config::fmt_offset(zone.offset),
+            zone.local,
+            label
+        );
+    }
+    s.push_str("])\n");
+    s
+}
+
+// write the starter config into '<OS_CONFIGS_LOCATION>/mvtime/<name>.ron'
+pub fn run(name: &str, zones: &[String], force: bool) -> Result<PathBuf> {
+    let now = Utc::now();
+    let mut tracks = vec![];
+    match local_zone() {
+        Some(tz) => tracks.push(Zone::new(tz, now, true)),
+        None => {
+            // not an IANA zone, take the offset only
+            let seconds = chrono::Local::now().offset().local_minus_utc();
+            tracks.push(Zone {
+                name: "Local".to_string(),
+                shortname: "LOC".to_string(),
+                offset: ((seconds / 3600) as i16, (seconds % 3600 / 60) as i16),
+                local: true,
+            });
+        }
+    }
+    for zone in zones {
+        tracks.push(Zone::new(parse_zone(zone)?, now, false));
+    }
+
+    let dir = config::config_dir().ok_or_else(|| eyre::eyre!("No OS config directory"))?;
+    let path = config_path(&dir, name)?;
+    if path.exists() && !force {
+        eyre::bail!("{:?} already exists, use --force to overwrite it", path);
+    }
+    std::fs::create_dir_all(&dir).wrap_err_with(|| format!("Failed to create {:?}", dir))?;
+    std::fs::write(&path, starter_config(&tracks, now))
+        .wrap_err_with(|| format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::{check, config::Config};
+
+    #[test]
+    fn zone_offset_follows_dst() {
+        let winter = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2026, 7, 15, 12, 0, 0).unwrap();
+        let tz = parse_zone("America/New_York").unwrap();
+        assert_eq!(Zone::new(tz, winter, false).offset, (-5, 0));
+        assert_eq!(Zone::new(tz, summer, false).offset, (-4, 0));
+        assert_eq!(Zone::new(tz, summer, false).name, "New York");
+        assert_eq!(Zone::new(parse_zone("Asia/Kolkata").unwrap(), winter, true).offset, (5, 30));
+        assert!(parse_zone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn config_names() {
+        let dir = Path::new("/configs");
+        assert_eq!(config_path(dir, "team").unwrap(), dir.join("team.ron"));
+        assert_eq!(config_path(dir, "team.toml").unwrap(), dir.join("team.ron"));
+        for name in ["", ".", "..", "../x", "a/b", "/etc/x"] {
+            assert!(config_path(dir, name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn starter_config_is_valid() {
+        let now = Utc::now();
+        let zones: Vec<Zone> = ["Europe/Berlin", "Asia/Tokyo", "America/St_Johns"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Zone::new(parse_zone(name).unwrap(), now, i == 0))
+            .collect();
+        let text = starter_config(&zones, now);
+        assert!(text.contains(r#"offset: "UTC-02:30""#) || text.contains(r#"offset: "UTC-03:30""#));
+        let mut config: Config = ron::from_str(&text).unwrap();
+        config.tracks.iter_mut().for_each(|t| t.ranges = config.templates["workday"].clone());
+        assert_eq!(config.tracks.len(), 3);
+        assert!(check::problems(&config).iter().all(|p| p.severity != check::Severity::Error));
+    }
+}
@@ -0,0 +1,296 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+// Live mode actions, bound to keys in the config `keys` section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Close,
+    SelectNext,
+    SelectPrev,
+    ScrollDown,
+    ScrollUp,
+    Detail,
+    Seconds,
+    Names,
+    Theme,
+    Config,
+    Reload,
+    Dismiss,
+    Help,
+    Prompt,
+    Overlap,
+    ZoomIn,
+    ZoomOut,
+}
+
+impl Action {
+    // in the order they are listed in the help overlay
+    pub const ALL: [Action; 18] = [
+        Action::Quit,
+        Action::Close,
+        Action::SelectNext,
+        Action::SelectPrev,
+        Action::ScrollDown,
+        Action::ScrollUp,
+        Action::Detail,
+        Action::Seconds,
+        Action::Names,
+        Action::Theme,
+        Action::Config,
+        Action::Reload,
+        Action::Dismiss,
+        Action::Help,
+        Action::Prompt,
+        Action::Overlap,
+        Action::ZoomIn,
+        Action::ZoomOut,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Close => "close the help/details, back to now, quit otherwise",
+            Action::SelectNext => "select the next track",
+            Action::SelectPrev => "select the previous track",
+            Action::ScrollDown => "scroll tracks down",
+            Action::ScrollUp => "scroll tracks up",
+            Action::Detail => "open/close the track details",
+            Action::Seconds => "show/hide seconds",
+            Action::Names => "full/short track names",
+            Action::Theme => "next theme",
+            Action::Config => "next config",
+            Action::Reload => "reload the config",
+            Action::Dismiss => "dismiss the error",
+            Action::Help => "open/close this help",
+            Action::Prompt => "show the time of '14:00 London' on all tracks",
+            Action::Overlap => "show/hide the shared windows of the tracks",
+            Action::ZoomIn => "shorter time span of the bars",
+            Action::ZoomOut => "longer time span of the bars, up to a week",
+        }
+    }
+
+    // keys of the actions missing in the config
+    pub fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "Ctrl-c"],
+            Action::Close => &["Esc"],
+            Action::SelectNext => &["j", "Down"],
+            Action::SelectPrev => &["k", "Up"],
+            Action::ScrollDown => &["PageDown"],
+            Action::ScrollUp => &["PageUp"],
+            Action::Detail => &["Enter"],
+            Action::Seconds => &["s"],
+            Action::Names => &["n"],
+            Action::Theme => &["t"],
+            Action::Config => &["Tab"],
+            Action::Reload => &["r"],
+            Action::Dismiss => &["x"],
+            Action::Help => &["?"],
+            Action::Prompt => &[":", "/"],
+            Action::Overlap => &["o"],
+            Action::ZoomIn => &["+", "="],
+            Action::ZoomOut => &["-"],
+        }
+    }
+}
+
+// Key with modifiers, e.g. "q", "Ctrl-c", "Alt+Enter", "F5".
+// Letter case is the shift state of the character keys, so "Q" is the same as "Shift-q".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("Esc", KeyCode::Esc),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Backspace", KeyCode::Backspace),
+    ("Space", KeyCode::Char(' ')),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Delete", KeyCode::Delete),
+];
+
+const MODIFIERS: [(&str, KeyModifiers); 3] =
+    [("Ctrl", KeyModifiers::CONTROL), ("Alt", KeyModifiers::ALT), ("Shift", KeyModifiers::SHIFT)];
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // shift is carried by the character case, crossterm reports it inconsistently
+        let (code, mut modifiers) = match (code, modifiers.contains(KeyModifiers::SHIFT)) {
+            (KeyCode::Char(c), true) => (KeyCode::Char(c.to_ascii_uppercase()), modifiers),
+            (KeyCode::Tab, true) => (KeyCode::BackTab, modifiers),
+            (code, _) => (code, modifiers),
+        };
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rest = text;
+        let mut modifiers = KeyModifiers::NONE;
+        'prefixes: loop {
+            for (name, modifier) in MODIFIERS {
+                let Some(prefix) = rest.get(..name.len() + 1) else {
+                    continue;
+                };
+                let separated = prefix.ends_with(['-', '+']) && rest.len() > prefix.len();
+                if separated && prefix[..name.len()].eq_ignore_ascii_case(name) {
+                    modifiers.insert(modifier);
+                    rest = &rest[prefix.len()..];
+                    continue 'prefixes;
+                }
+            }
+            break;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match NAMED_KEYS.iter().find(|(name, _)| name.eq_ignore_ascii_case(rest)) {
+                Some((_, code)) => *code,
+                None => match rest.get(1..).and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if rest.starts_with(['F', 'f']) && (1..=12).contains(&n) => {
+                        KeyCode::F(n)
+                    }
+                    _ => return Err(format!("unknown key {:?}", text)),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    #[inline]
+    pub fn matches(&self, key: KeyEvent) -> bool {
+        *self == Self::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}-", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::BackTab => f.write_str("Shift-Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Char(c) if c != ' ' => write!(f, "{}", c),
+            code => match NAMED_KEYS.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+// Config key bindings over the default ones, an action listed in the config loses its default keys
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: Vec<(Action, Vec<KeyChord>)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+impl KeyMap {
+    // keys that don't parse are left out, see `check::problems`
+    pub fn new(keys: &BTreeMap<Action, Vec<String>>) -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| {
+                let chords = match keys.get(action) {
+                    Some(keys) => keys.iter().filter_map(|k| KeyChord::parse(k).ok()).collect(),
+                    None => action
+                        .default_keys()
+                        .iter()
+                        .filter_map(|k| KeyChord::parse(k).ok())
+                        .collect(),
+                };
+                (*action, chords)
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, chords)| chords.iter().any(|chord| chord.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyChord] {
+        self.bindings.iter().find(|(a, _)| *a == action).map_or(&[], |(_, chords)| chords)
+    }
+
+    // actions other than `action` bound to `chord`
+    pub fn others(&self, action: Action, chord: KeyChord) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(a, chords)| *a != action && chords.contains(&chord))
+            .map(|(a, _)| *a)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn chords_parsed_and_matched() {
+        let ctrl_c = KeyChord::parse("Ctrl-c").unwrap();
+        assert!(ctrl_c.matches(key(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!ctrl_c.matches(key(KeyCode::Char('c'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("ctrl+C").unwrap().to_string(), "Ctrl-C");
+        assert_eq!(KeyChord::parse("Shift-q").unwrap(), KeyChord::parse("Q").unwrap());
+        assert!(KeyChord::parse("?")
+            .unwrap()
+            .matches(key(KeyCode::Char('?'), KeyModifiers::SHIFT)));
+        assert!(KeyChord::parse("Shift-Tab")
+            .unwrap()
+            .matches(key(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert_eq!(KeyChord::parse("pagedown").unwrap().to_string(), "PageDown");
+        assert_eq!(KeyChord::parse("Alt-F5").unwrap().to_string(), "Alt-F5");
+        assert_eq!(KeyChord::parse("-").unwrap().to_string(), "-");
+        assert_eq!(KeyChord::parse("Ctrl--").unwrap().to_string(), "Ctrl--");
+        assert!(KeyChord::parse("Hyper-x").is_err());
+        assert!(KeyChord::parse("F13").is_err());
+        assert!(KeyChord::parse("").is_err());
+    }
+
+    #[test]
+    fn config_keys_replace_defaults() {
+        let keys = BTreeMap::from([(Action::Quit, vec!["Ctrl-q".to_string()])]);
+        let map = KeyMap::new(&keys);
+        assert_eq!(map.action(key(KeyCode::Char('q'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(map.action(key(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+        assert_eq!(map.action(key(KeyCode::Char('t'), KeyModifiers::NONE)), Some(Action::Theme));
+        let default = KeyMap::default();
+        assert_eq!(default.action(key(KeyCode::Esc, KeyModifiers::NONE)), Some(Action::Close));
+        let t = KeyChord::parse("t").unwrap();
+        assert_eq!(default.others(Action::Help, t), vec![Action::Theme]);
+    }
+}
@@ -1,6 +1,10 @@
-use chrono::{self, DateTime, Timelike, Utc};
-use config::{Colors, Config, TimeRange};
+use chrono::{self, DateTime, Datelike, NaiveDate, Timelike, Utc};
+use color::ColorSupport;
+use config::{ColorSpec, Config, Span, TimeRange, TimeTrack};
 use eyre::{Result, WrapErr};
+use keys::KeyMap;
+use std::path::PathBuf;
+use theme::Theme;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -8,9 +12,21 @@ use tui::{
     terminal::CompletedFrame,
     Terminal,
 };
-use ui::LineAux;
+use ui::{LineAux, Notice};
+pub mod check;
+pub mod color;
 pub mod config;
+pub mod format;
+pub mod ics;
+pub mod init;
+pub mod keys;
+pub mod overlap;
+pub mod print;
+pub mod query;
+pub mod solar;
+pub mod theme;
 pub mod ui;
+pub mod watch;
 
 pub const MINUTES_PER_DAY: u16 = to_minutes((24, 00));
 
@@ -27,8 +43,14 @@ pub fn to_hour_minute(minutes: u16) -> (u16, u16) {
 // map point to time given time axes width in units
 #[inline]
 pub fn point_to_time(idx: u16, width: u16) -> u16 {
+    point_to_span_time(idx, width, MINUTES_PER_DAY)
+}
+
+// same for the time since the start of a span of `span` minutes
+#[inline]
+pub fn point_to_span_time(idx: u16, width: u16, span: u16) -> u16 {
     debug_assert_ne!(width, 0, "Hoy! 0 width, init?");
-    let ratio = f64::from(MINUTES_PER_DAY) / f64::from(width);
+    let ratio = f64::from(span) / f64::from(width);
     (f64::from(idx) * ratio).round() as u16
 }
 
@@ -39,35 +61,109 @@ pub fn time_to_range<R: AsRef<[TimeRange]>>(time: u16, ranges: R) -> Option<usiz
     ranges.as_ref().iter().position(|r| (to_minutes(r.start)..to_minutes(r.end)).contains(&time))
 }
 
+// minutes from `time` to the next range border, None if a single range takes the whole day.
+// The day end is not a border when the ranges on both sides of midnight are gaps, or the same
+// generated night or twilight.
+pub fn next_transition(time: u16, ranges: &[TimeRange]) -> Option<u16> {
+    if ranges.len() < 2 {
+        return None;
+    }
+    let current = time_to_range(time, ranges)?;
+    let mut end = to_minutes(ranges[current].end);
+    let (last, first) = (&ranges[ranges.len() - 1], &ranges[0]);
+    let joined = last.auto() && last.gap == first.gap && last.daylight == first.daylight;
+    if end == MINUTES_PER_DAY && joined {
+        end += to_minutes(ranges[0].end);
+    }
+    Some(end - time)
+}
+
 // given sequence of time ranges, fill in-between gaps to cover full range of (0,0) - (24,0).
 // assumes provided ranges are valid:
 // - start and end is within the interval [00:00, 24:00]
 // - start << end
 // - ranges are non-overlapping and chronologically ordered
 #[inline]
-pub fn fill_gaps(ranges: &mut Vec<TimeRange>, colors: &Colors) {
+pub fn fill_gaps(ranges: &mut Vec<TimeRange>, base: Color) {
     let mut end = 0;
+    let mut i = 0;
 
-    for mut i in 0..ranges.len() {
+    while i < ranges.len() {
         if to_minutes(ranges[i].start) > end {
-            ranges.insert(
-                i,
-                TimeRange::default()
-                    .start(to_hour_minute(end))
-                    .end(ranges[i].start)
-                    .color(colors.base),
-            );
+            ranges.insert(i, TimeRange::gap(to_hour_minute(end), ranges[i].start, base));
             i += 1;
         }
         end = to_minutes(ranges[i].end);
+        i += 1;
     }
 
     if ranges.is_empty() || end < MINUTES_PER_DAY {
-        ranges
-            .push(TimeRange::default().start(to_hour_minute(end)).end((24, 00)).color(colors.base));
+        ranges.push(TimeRange::gap(to_hour_minute(end), (24, 00), base));
     }
 }
 
+// `ranges` with the parts under `over` cut out, along with `over`, in chronological order
+fn overlay(ranges: Vec<TimeRange>, over: Vec<TimeRange>) -> Vec<TimeRange> {
+    let mut result = vec![];
+    for range in ranges {
+        let mut pieces = vec![(to_minutes(range.start), to_minutes(range.end))];
+        for top in &over {
+            let (top_start, top_end) = (to_minutes(top.start), to_minutes(top.end));
+            pieces = pieces
+                .into_iter()
+                .flat_map(|(start, end)| [(start, end.min(top_start)), (start.max(top_end), end)])
+                .filter(|(start, end)| start < end)
+                .collect();
+        }
+        for (start, end) in pieces {
+            let (start, end) = (to_hour_minute(start), to_hour_minute(end));
+            result.push(TimeRange { start, end, ..range.clone() });
+        }
+    }
+    result.extend(over);
+    result.sort_by_key(|range| to_minutes(range.start));
+    result
+}
+
+// Ranges of a dated track for the local `date`: the override of the date or the regular ones,
+// under the calendar events of the date, then the night and twilight where there are no ranges
+// for a track with a location, and the gaps.
+pub fn date_ranges(track: &TimeTrack, date: NaiveDate, theme: &Theme) -> Vec<TimeRange> {
+    let mut ranges = match track.overrides.iter().find(|o| o.date == date) {
+        Some(day) => day.ranges.clone(),
+        None => track.regular.clone(),
+    };
+    if let Some(calendar) = &track.calendar {
+        let events = ics::day_spans(&calendar.events, date, track.offset())
+            .into_iter()
+            .map(|(start, end, i)| {
+                let (start, end) = (to_hour_minute(start), to_hour_minute(end));
+                TimeRange::new(start, end, calendar.colors[i])
+            })
+            .collect();
+        ranges = overlay(ranges, events);
+    }
+    if let Some(location) = track.location {
+        let own: Vec<(u16, u16)> =
+            ranges.iter().map(|r| (to_minutes(r.start), to_minutes(r.end))).collect();
+        let taken = |minute| own.iter().any(|(start, end)| (*start..*end).contains(&minute));
+        let offset = track.offset().num_minutes();
+        for (start, end, kind) in solar::ranges(location, date, offset, taken) {
+            let (start, end) = (to_hour_minute(start), to_hour_minute(end));
+            ranges.push(TimeRange::daylight(start, end, kind, theme));
+        }
+        ranges.sort_by_key(|range| to_minutes(range.start));
+    }
+    fill_gaps(&mut ranges, theme.base);
+    ranges
+}
+
+// switch a dated track to the ranges of the local `date`
+pub fn apply_date(track: &mut TimeTrack, date: NaiveDate, theme: &Theme) {
+    track.ranges = date_ranges(track, date, theme);
+    track.date = Some(date);
+}
+
 pub struct App {
     config: Config,
     min_title_width: u16,
@@ -78,29 +174,73 @@ pub struct App {
     visible_lines: usize,
     seconds: u16,
     renderable: bool,
+    notice: Option<Notice>,
+    theme_name: String,
+    theme: Theme,
+    color_support: ColorSupport,
+    // highlighted track and whether its detail pane is open
+    selected: Option<usize>,
+    detail: bool,
+    // offset of the system time zone, the detail pane shows the range times in it as well
+    reference: chrono::Duration,
+    help: bool,
+    // time of the last config reload and its error, if it failed
+    reloaded: Option<(DateTime<Utc>, Option<String>)>,
+    keys: KeyMap,
+    // last laid out screen area
+    screen: Rect,
+    // first visible track
+    scroll: usize,
+    // names picked by the user over the ones fitting the screen
+    full_names: Option<bool>,
+    wide_titles: bool,
+    // time query being typed and its error
+    prompt: Option<(String, Option<String>)>,
+    // inspected instant and the query it came from, shown instead of the current time
+    at: Option<(DateTime<Utc>, String)>,
+    // tracks sharing the highlighted windows and the windows, see `overlap::windows`
+    band: Option<Band>,
+    // time the bars cover
+    span: Span,
+}
+
+// track indices and their shared windows
+type Band = (Vec<usize>, Vec<(u16, u16)>);
+
+// minimum displayable screen width
+// title _ notch(1) bar(1) clock bar(1) notch(1) __, the clock is the widest of the time labels
+#[inline]
+fn min_width(min_title: u16, config: &Config) -> u16 {
+    let min_clock = config.tracks.iter().map(|t| t.time_label.width()).max().unwrap_or(7);
+    min_title + 1 + 1 + 1 + min_clock + 1 + 1 + 2
 }
 
 impl App {
     pub fn new(mut config: Config) -> Self {
         // Tracks preprocessing and layout initialization
 
-        // min/max title and clock columns width
+        // min/max title columns width
         // max: badge(1) _ longest(name)
         // min: badge(1) _ longest(shortname)
-        // clock: 10 if any of the `seconds` is set to true, 7 otherwise
         let mut max_title = u16::MIN;
         let mut min_title = u16::MIN;
-        let mut min_clock = 7;
+        // validated config has a known theme
+        let theme_name = config.theme_name().to_string();
+        let theme = config.theme(&theme_name).unwrap_or_default();
+        let now = Utc::now();
         for track in &mut config.tracks {
             max_title = u16::max(max_title, track.name.len() as u16 + 2);
             min_title = u16::max(min_title, track.shortname.len() as u16 + 2);
-            min_clock = u16::max(min_clock, track.time_label.seconds as u16 * 10);
             // fill time range gaps, so ranges cover whole day
-            fill_gaps(&mut track.ranges, &config.colors);
+            if track.dated() {
+                track.regular = std::mem::take(&mut track.ranges);
+                apply_date(track, (now + track.offset()).date_naive(), &theme);
+            } else {
+                fill_gaps(&mut track.ranges, theme.base);
+            }
         }
         // minimum displayable screen size
-        // width: title _ notch(1) bar(1) clock(7/10) bar(1) notch(1) __
-        let min_width = min_title + 1 + 1 + 1 + min_clock + 1 + 1 + 2;
+        let min_width = min_width(min_title, &config);
         // height: margin(1) line(1) margin(1)
         let min_height = 3;
 
@@ -109,6 +249,8 @@ impl App {
 
         Self {
             lines: vec![LineAux::default(); config.tracks.len()],
+            keys: KeyMap::new(&config.keys),
+            span: config.span,
             config,
             min_title_width: min_title,
             max_title_width: max_title,
@@ -117,12 +259,414 @@ impl App {
             visible_lines: 0,
             seconds: 0,
             renderable: false,
+            notice: None,
+            theme_name,
+            theme,
+            color_support: ColorSupport::TrueColor,
+            selected: None,
+            detail: false,
+            reference: chrono::Duration::seconds(
+                chrono::Local::now().offset().local_minus_utc().into(),
+            ),
+            help: false,
+            reloaded: None,
+            screen: Rect::default(),
+            scroll: 0,
+            full_names: None,
+            wide_titles: false,
+            prompt: None,
+            at: None,
+            band: None,
+        }
+    }
+
+    // colors are mapped to the nearest supported ones on render
+    pub fn set_color_support(&mut self, support: ColorSupport) {
+        self.color_support = support;
+    }
+
+    pub fn theme_name(&self) -> &str {
+        &self.theme_name
+    }
+
+    // switch to a preset or config theme, false if there is no such theme
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        let Some(theme) = self.config.theme(name) else {
+            return false;
+        };
+        for range in self.config.tracks.iter_mut().flat_map(|t| t.ranges.iter_mut()) {
+            if range.gap {
+                range.color = theme.base;
+            }
+            if let Some(kind) = range.daylight {
+                range.color = theme.daylight(kind);
+            }
+        }
+        // ranges of the other days are taken again on the next tick
+        for aux in &mut self.lines {
+            aux.days_from = None;
+        }
+        self.theme_name = name.to_string();
+        self.theme = theme;
+        true
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn set_span(&mut self, span: Span) {
+        self.span = span;
+    }
+
+    // the next shorter or longer span, returns the new one
+    pub fn zoom(&mut self, zoom_in: bool) -> Span {
+        self.span = self.span.zoom(zoom_in);
+        self.span
+    }
+
+    // switch to the theme following the current one, returns its name
+    pub fn next_theme(&mut self) -> &str {
+        let names = self.config.theme_names();
+        let current = names.iter().position(|n| *n == self.theme_name).unwrap_or(0);
+        let next = names[(current + 1) % names.len()].to_string();
+        self.set_theme(&next);
+        &self.theme_name
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    // move the highlight one track down, the first key press selects the top visible one
+    pub fn select_next(&mut self) {
+        if self.visible_lines > 0 {
+            let last = self.config.tracks.len() - 1;
+            let next = self.selected.map_or(self.scroll, |i| usize::min(i + 1, last));
+            self.select(next);
+        }
+    }
+
+    // move the highlight one track up, the first key press selects the bottom visible one
+    pub fn select_prev(&mut self) {
+        if self.visible_lines > 0 {
+            let last = self.scroll + self.visible_lines - 1;
+            let prev = self.selected.map_or(last, |i| i.saturating_sub(1));
+            self.select(prev);
+        }
+    }
+
+    // select the track and scroll it into the view
+    fn select(&mut self, track: usize) {
+        self.selected = Some(track);
+        if track < self.scroll {
+            self.scroll = track;
+        } else if track >= self.scroll + self.visible_lines {
+            self.scroll = track + 1 - self.visible_lines;
+        }
+        self.update_layout(self.screen);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll += 1;
+        self.update_layout(self.screen);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+        self.update_layout(self.screen);
+    }
+
+    // show seconds on all the tracks, or on none if any of them has them
+    pub fn toggle_seconds(&mut self) {
+        let seconds = !self.config.tracks.iter().any(|t| t.time_label.seconds);
+        for track in &mut self.config.tracks {
+            track.time_label.seconds = seconds;
+        }
+        self.min_width = min_width(self.min_title_width, &self.config);
+        self.update_layout(self.screen);
+    }
+
+    // switch between full and short track names, full ones are shown only if they fit
+    pub fn toggle_names(&mut self) {
+        let full = self.full_names.unwrap_or(self.wide_titles);
+        self.full_names = Some(!full);
+        self.update_layout(self.screen);
+    }
+
+    pub fn prompt_open(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    pub fn open_prompt(&mut self) {
+        self.prompt = Some((String::new(), None));
+    }
+
+    pub fn close_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    pub fn prompt_push(&mut self, c: char) {
+        if let Some((text, error)) = &mut self.prompt {
+            text.push(c);
+            *error = None;
+        }
+    }
+
+    pub fn prompt_pop(&mut self) {
+        if let Some((text, error)) = &mut self.prompt {
+            text.pop();
+            *error = None;
+        }
+    }
+
+    // inspect the queried instant, the prompt stays open with the error if the query is invalid
+    pub fn submit_prompt(&mut self, now: DateTime<Utc>) {
+        let Some((text, error)) = &mut self.prompt else {
+            return;
+        };
+        match query::parse(text, &self.config.tracks, self.reference, now) {
+            Ok(at) => {
+                self.at = Some((at, text.trim().to_string()));
+                self.prompt = None;
+            }
+            Err(e) => *error = Some(e),
+        }
+    }
+
+    // every track's local time and range at the queried time, see `query::parse`
+    pub fn query(&mut self, query: &str, now: DateTime<Utc>) -> Result<Vec<String>, String> {
+        let at = query::parse(query, &self.config.tracks, self.reference, now)?;
+        for track in self.config.tracks.iter_mut().filter(|t| t.dated()) {
+            apply_date(track, (at + track.offset()).date_naive(), &self.theme);
+        }
+        Ok(query::table(&self.config.tracks, at))
+    }
+
+    pub fn inspecting(&self) -> bool {
+        self.at.is_some()
+    }
+
+    pub fn back_to_now(&mut self) {
+        self.at = None;
+    }
+
+    // shared windows of the tracks by name, all the tracks if there are no names,
+    // with the ranges of the local `date` or the current ones
+    fn overlap_windows(
+        &self,
+        names: &[String],
+        range: Option<&ColorSpec>,
+        date: Option<NaiveDate>,
+    ) -> Result<Band, String> {
+        let tracks = match names {
+            [] => (0..self.config.tracks.len()).collect(),
+            names => names
+                .iter()
+                .map(|name| query::find_track(name, &self.config.tracks))
+                .collect::<Result<Vec<usize>, String>>()?,
+        };
+        let color = match range {
+            Some(ColorSpec::Palette(name)) if !self.config.palette.contains_key(name) => {
+                return Err(check::unknown_color(name, &self.config));
+            }
+            Some(spec) => self.config.color(spec),
+            None => None,
+        };
+        let ranges: Vec<Vec<TimeRange>> = tracks
+            .iter()
+            .map(|i| match (&self.config.tracks[*i], date) {
+                (track, Some(date)) if track.dated() => date_ranges(track, date, &self.theme),
+                (track, _) => track.ranges.clone(),
+            })
+            .collect();
+        let refs: Vec<(&TimeTrack, &[TimeRange])> = tracks
+            .iter()
+            .zip(&ranges)
+            .map(|(i, ranges)| (&self.config.tracks[*i], ranges.as_slice()))
+            .collect();
+        let windows = overlap::windows(&refs, color);
+        Ok((tracks, windows))
+    }
+
+    // band of the tracks' new local day, the ones out of view switch along with the visible ones
+    fn refresh_band(&mut self, now: DateTime<Utc>) {
+        let Some((tracks, _)) = self.band.take() else {
+            return;
+        };
+        for i in tracks {
+            let track = &mut self.config.tracks[i];
+            let date = (now + track.offset()).date_naive();
+            if track.dated() && track.date != Some(date) {
+                apply_date(track, date, &self.theme);
+            }
+        }
+        let overlap = self.config.overlap.clone().unwrap_or_default();
+        self.band = self.overlap_windows(&overlap.tracks, overlap.range.as_ref(), None).ok();
+    }
+
+    // highlight the shared windows of the config `overlap` tracks, returns the number of windows
+    // when switched on
+    pub fn toggle_overlap(&mut self) -> Option<usize> {
+        if self.band.take().is_some() {
+            return None;
+        }
+        let overlap = self.config.overlap.clone().unwrap_or_default();
+        // validated config has known tracks and colors
+        self.band = self.overlap_windows(&overlap.tracks, overlap.range.as_ref(), None).ok();
+        self.band.as_ref().map(|(_, windows)| windows.len())
+    }
+
+    // shared windows in UTC and in every track's time, see `overlap::windows`,
+    // on the local `date` of the tracks or their current day
+    pub fn overlap(
+        &self,
+        names: &[String],
+        range: Option<&str>,
+        date: Option<NaiveDate>,
+    ) -> Result<Vec<String>, String> {
+        let range = range.map(ColorSpec::from_name).transpose()?;
+        let (tracks, windows) = self.overlap_windows(names, range.as_ref(), date)?;
+        let hh_mm = |minutes: i64| {
+            let (hour, minute) = to_hour_minute(minutes.rem_euclid(MINUTES_PER_DAY.into()) as u16);
+            format!("{:02}:{:02}", hour, minute)
+        };
+        let width = tracks.iter().map(|i| self.config.tracks[*i].name.len()).max().unwrap_or(0);
+        let mut lines = vec![];
+        for (start, end) in windows {
+            let (start, end) = (i64::from(start), i64::from(end));
+            let length = end - start;
+            lines.push(format!(
+                "UTC {}-{}  {}h {:02}m",
+                hh_mm(start),
+                hh_mm(end),
+                length / 60,
+                length % 60
+            ));
+            for track in tracks.iter().map(|i| &self.config.tracks[*i]) {
+                let offset = track.offset().num_minutes();
+                let local = format!("{}-{}", hh_mm(start + offset), hh_mm(end + offset));
+                lines.push(format!("  {:<width$}  {}", track.name, local, width = width));
+            }
+        }
+        Ok(lines)
+    }
+
+    pub fn keys(&self) -> &KeyMap {
+        &self.keys
+    }
+
+    pub fn detail_open(&self) -> bool {
+        self.detail
+    }
+
+    // open the detail pane of the selected track, or close an open one
+    pub fn toggle_detail(&mut self) {
+        self.detail = !self.detail && self.selected.is_some();
+    }
+
+    pub fn close_detail(&mut self) {
+        self.detail = false;
+    }
+
+    pub fn help_open(&self) -> bool {
+        self.help
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.help = !self.help;
+    }
+
+    // record a config reload for the help overlay, with the error if it failed
+    pub fn set_reloaded(&mut self, at: DateTime<Utc>, error: Option<String>) {
+        self.reloaded = Some((at, error));
+    }
+
+    fn reload_status(&self) -> String {
+        let time = |at: &DateTime<Utc>| at.with_timezone(&chrono::Local).format("%H:%M:%S");
+        match &self.reloaded {
+            None => "watching for changes".to_string(),
+            Some((at, None)) => format!("reloaded at {}", time(at)),
+            Some((at, Some(error))) => format!("failed at {}: {}", time(at), error),
+        }
+    }
+
+    pub fn track_count(&self) -> usize {
+        self.config.tracks.len()
+    }
+
+    // config file followed by its includes
+    pub fn config_files(&self) -> &[PathBuf] {
+        &self.config.files
+    }
+
+    // Events of the track ranges for `days` local days starting with the one of `from`, gaps and
+    // the night and twilight left out. Date overrides and calendar events are taken for each day.
+    pub fn schedule(&mut self, from: DateTime<Utc>, days: u32) -> Vec<ics::Entry> {
+        let mut entries = vec![];
+        for track in &mut self.config.tracks {
+            let first = (from + track.offset()).date_naive();
+            for date in first.iter_days().take(days as usize) {
+                if track.dated() {
+                    apply_date(track, date, &self.theme);
+                }
+                let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default() - track.offset();
+                let utc = |time| {
+                    let minutes = chrono::Duration::minutes(to_minutes(time).into());
+                    DateTime::from_naive_utc_and_offset(midnight + minutes, Utc)
+                };
+                for range in track.ranges.iter().filter(|range| !range.auto()) {
+                    let span = format!(
+                        "{:02}:{:02}-{:02}:{:02}",
+                        range.start.0, range.start.1, range.end.0, range.end.1
+                    );
+                    entries.push(ics::Entry {
+                        uid: format!(
+                            "{}-{}-{:02}{:02}@mvtime",
+                            track.shortname.to_lowercase().replace(char::is_whitespace, "-"),
+                            date.format("%Y%m%d"),
+                            range.start.0,
+                            range.start.1
+                        ),
+                        start: utc(range.start),
+                        end: utc(range.end),
+                        summary: track.name.clone(),
+                        description: format!(
+                            "{} {} {}",
+                            track.shortname,
+                            span,
+                            config::fmt_offset(track.offset)
+                        ),
+                        categories: match &range.color_spec {
+                            ColorSpec::Palette(name) => vec![name.clone()],
+                            ColorSpec::Color(_) => vec![],
+                        },
+                    });
+                }
+            }
         }
+        entries.sort_by_key(|entry| entry.start);
+        entries
+    }
+
+    // config files along with the track calendars, reloaded on change
+    pub fn watched_files(&self) -> Vec<PathBuf> {
+        let calendars = self.config.tracks.iter().filter_map(|t| t.calendar.as_ref());
+        self.config.files.iter().cloned().chain(calendars.map(|c| c.path.clone())).collect()
+    }
+
+    pub fn notify(&mut self, notice: Notice) {
+        self.notice = Some(notice);
+    }
+
+    pub fn dismiss(&mut self) {
+        self.notice = None;
     }
 
     // Screen size related computations
     // executes on 'resize' event
     pub fn update_layout(&mut self, mut inner: Rect) {
+        self.screen = inner;
         if inner.width < self.min_width || inner.height < self.min_height {
             self.renderable = false;
             return;
@@ -134,8 +678,14 @@ impl App {
         inner.width -= 2;
 
         // title col width
-        // if `screen width / max title len` > 4 => use 'name', otherwise use 'shortname'
-        let wide_title = inner.width as f64 / self.max_title_width as f64 > 4.;
+        // if `screen width / max title len` > 4 => use 'name', otherwise use 'shortname',
+        // names picked by the user are used if they fit
+        let fits = inner.width + 2 >= self.min_width - self.min_title_width + self.max_title_width;
+        let wide_title = match self.full_names {
+            Some(full) => full && fits,
+            None => inner.width as f64 / self.max_title_width as f64 > 4.,
+        };
+        self.wide_titles = wide_title;
         let title_width =
             wide_title as u16 * self.max_title_width + !wide_title as u16 * self.min_title_width;
 
@@ -147,6 +697,7 @@ impl App {
         // number of visible tracks is limited by screen height
         let nlines = usize::min(inner.height as usize, self.config.tracks.len());
         self.visible_lines = nlines;
+        self.scroll = usize::min(self.scroll, self.config.tracks.len() - nlines);
 
         // column layout
         let chunks = Layout::default()
@@ -176,10 +727,10 @@ impl App {
             "chart should be divisible by clock column width into two equal halves"
         );
 
-        // line rects
+        // line rects of the visible tracks
         for i in 0..nlines {
-            let track = &self.config.tracks[i as usize];
-            let aux = &mut self.lines[i];
+            let track = &self.config.tracks[self.scroll + i];
+            let aux = &mut self.lines[self.scroll + i];
 
             aux.title_text.clear();
             aux.title_text.push_str([&track.shortname, &track.name][wide_title as usize]);
@@ -193,8 +744,9 @@ impl App {
             // clock width:
             // _ hh : mm _
             // _ hh : mm : ss _
+            // _ hh : mm nn% _
             let mut clock_rect = clocks.intersection(line);
-            clock_rect.width = 7 + track.time_label.seconds as u16 * 3;
+            clock_rect.width = track.time_label.width();
 
             aux.badge = badges.intersection(line);
             aux.title = titles.intersection(line);
@@ -210,7 +762,15 @@ impl App {
     // executes on 'tick' event, presumably once in a second
     pub fn tick(&mut self, now: DateTime<Utc>) {
         self.seconds = now.time().second() as u16;
-        for i in 0..self.visible_lines {
+        let mut switched = false;
+        for i in self.scroll..self.scroll + self.visible_lines {
+            // ranges of the new local day
+            let track = &mut self.config.tracks[i];
+            let date = (now + track.offset()).date_naive();
+            if track.dated() && track.date != Some(date) {
+                apply_date(track, date, &self.theme);
+                switched = true;
+            }
             let track = &self.config.tracks[i];
             let aux = &mut self.lines[i];
 
@@ -219,26 +779,56 @@ impl App {
             aux.local_time.0 = hour;
             aux.local_time.1 = minute;
 
+            // span of the bars, dated tracks take the ranges of every day in it
+            aux.window = self.span.window(hour, date.weekday());
+            let day = i32::from(MINUTES_PER_DAY);
+            let days = (aux.window.0.div_euclid(day)
+                ..(aux.window.0 + i32::from(aux.window.1) + day - 1) / day)
+                .map(|d| date + chrono::Duration::days(d.into()));
+            let first = days.clone().next();
+            if !track.dated() || aux.window.1 <= MINUTES_PER_DAY {
+                aux.days.clear();
+            } else if aux.days_from != first || aux.days.len() != days.len() {
+                aux.days = days.map(|date| date_ranges(track, date, &self.theme)).collect();
+            }
+            aux.days_from = first;
+
             // find current active range
             let minutes = to_minutes((hour, minute));
             let current_range_idx =
                 time_to_range(minutes, &track.ranges).expect("shouldn't fail neva-eva");
             aux.current_range = current_range_idx;
 
-            // compute bar widths and positions
+            // compute bar widths and positions,
+            // high resolution bars take partially covered cells, the rest are rounded to whole ones
+            aux.now = f64::from(minutes) + f64::from(self.seconds) / 60.;
+            let range = &track.ranges[current_range_idx];
+            let (start, end) =
+                (f64::from(to_minutes(range.start)), f64::from(to_minutes(range.end)));
+            aux.progress = (aux.now - start) / (end - start);
+
+            let hires = self.config.hires;
+            let at = if hires { aux.now } else { f64::from(minutes) };
+            // time since the span start
+            let at = at - f64::from(aux.window.0);
+            let span = f64::from(aux.window.1);
+            let cells = |width: f64| if hires { width.ceil() } else { width.round() };
             let left_width = aux.clock.left() - aux.chart.left();
-            let ratio = f64::from(left_width) / f64::from(MINUTES_PER_DAY);
-            let width = (f64::from(minutes) * ratio).round();
+            let ratio = f64::from(left_width) / span;
+            let width = cells(at * ratio);
             let width = u16::max(width as u16, 1);
             aux.bars.0.width = width;
             aux.bars.0.x = aux.clock.left() - width;
 
             let right_width = aux.chart.right() - aux.clock.right();
-            let ratio = f64::from(right_width) / f64::from(MINUTES_PER_DAY);
-            let width = (f64::from(MINUTES_PER_DAY - minutes) * ratio).round();
+            let ratio = f64::from(right_width) / span;
+            let width = cells((span - at) * ratio);
             let width = u16::max(width as u16, 1);
             aux.bars.1.width = width;
         }
+        if switched {
+            self.refresh_band(now);
+        }
     }
 
     pub fn render<'a, B: Backend>(
@@ -248,30 +838,74 @@ impl App {
         if !self.renderable {
             // screen size is not enough to display any meaningfull chart,
             // draw a blank screen
+            let background = self.color_support.map(self.theme.background);
             return terminal
-                .draw(|f| ui::fill(f, f.size(), Color::Reset))
+                .draw(|f| ui::fill(f, f.size(), background))
                 .wrap_err("Failed to draw a frame");
         }
 
         // compute new bar data
-        self.tick(chrono::offset::Utc::now());
+        let now = chrono::offset::Utc::now();
+        if self.notice.as_ref().is_some_and(|n| n.expired(now)) {
+            self.notice = None;
+        }
+        let now = self.at.as_ref().map_or(now, |(at, _)| *at);
+        self.tick(now);
 
         // draw ui
+        let mono = self.color_support == ColorSupport::Mono;
         terminal
             .draw(|frame| {
-                for (line, track) in self
+                ui::fill(frame, frame.size(), self.theme.background);
+                for (i, (line, track)) in self
                     .lines
                     .iter()
+                    .zip(self.config.tracks.iter())
+                    .enumerate()
+                    .skip(self.scroll)
                     .take(self.visible_lines)
-                    .zip(self.config.tracks.iter().take(self.visible_lines))
                 {
                     if track.show_badge {
-                        ui::render_badge(frame, line, track, &self.config.colors);
+                        ui::render_badge(frame, line, track, &self.theme);
+                    }
+                    ui::render_title(frame, line, &self.theme, self.selected == Some(i));
+                    ui::render_clock(frame, self.seconds, line, track, &self.theme, mono);
+                    let hires = self.config.hires && !mono;
+                    if hires {
+                        ui::render_hires_bars(frame, line, track, &self.theme);
+                    } else {
+                        ui::render_bars(frame, line, track, &self.theme, mono);
                     }
-                    ui::render_title(frame, line, &self.config.colors);
-                    ui::render_clock(frame, self.seconds, line, track, &self.config.colors);
-                    ui::render_bars(frame, line, track);
+                    if let Some((_, windows)) =
+                        self.band.as_ref().filter(|(tracks, _)| tracks.contains(&i))
+                    {
+                        ui::render_band(frame, line, track, windows, hires);
+                    }
+                }
+                if let Some(track) = self.selected.filter(|_| self.detail) {
+                    let track = &self.config.tracks[track];
+                    ui::render_detail(frame, track, now, self.reference, &self.theme);
+                }
+                if let Some((text, error)) = &self.prompt {
+                    let text = format!(":{}▏", text);
+                    ui::render_prompt(frame, &text, error.as_deref(), &self.theme);
+                } else if let Some((_, query)) = &self.at {
+                    let text = match self.keys.keys(keys::Action::Close).first() {
+                        Some(key) => format!("at {}, {} to get back to now", query, key),
+                        None => format!("at {}", query),
+                    };
+                    ui::render_prompt(frame, &text, None, &self.theme);
                 }
+                if self.help {
+                    let config = self.config.files.first().map(|p| p.display().to_string());
+                    let status = self.reload_status();
+                    let config = config.unwrap_or_default();
+                    ui::render_help(frame, &self.keys, &config, &status, &self.theme);
+                }
+                if let Some(notice) = &self.notice {
+                    ui::render_notice(frame, notice, &self.theme);
+                }
+                ui::downsample(frame, self.color_support);
             })
             .wrap_err("Failed to draw a frame")
     }
@@ -336,6 +970,7 @@ mod tests {
     // time_to_range
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn simple_range_inclusive() {
         let ranges = vec![TimeRange::new((0, 0), (24, 0), Color::Reset)];
         assert_eq!(time_to_range(0, &ranges[..]), Some(0));
@@ -343,12 +978,14 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn simple_range_out_of() {
         let ranges = vec![TimeRange::new((0, 0), (12, 0), Color::Reset)];
         assert_eq!(time_to_range(to_minutes((12, 0)), &ranges[..]), None);
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn simple_range2() {
         let ranges = vec![
             TimeRange::new((0, 0), (12, 0), Color::Reset),
@@ -358,6 +995,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn ranges_exclusive() {
         let ranges = vec![
             TimeRange::new((0, 0), (12, 0), Color::Reset),
@@ -369,6 +1007,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn ranges_non_contiguous() {
         let ranges = vec![
             TimeRange::new((0, 0), (1, 0), Color::Reset),
@@ -386,7 +1025,7 @@ mod tests {
     #[test]
     fn fill_empty_ranges() {
         let mut r = vec![];
-        fill_gaps(&mut r, &Default::default());
+        fill_gaps(&mut r, Color::DarkGray);
         assert_eq!(r.len(), 1);
         assert_eq!(to_minutes(r[0].start), 0);
         assert_eq!(to_minutes(r[0].end), to_minutes((24, 00)));
@@ -395,7 +1034,7 @@ mod tests {
     #[test]
     fn fill_full_range_shouldnt_do_any() {
         let mut r = vec![TimeRange::new((0, 0), (24, 0), Color::DarkGray)];
-        fill_gaps(&mut r, &Default::default());
+        fill_gaps(&mut r, Color::DarkGray);
         assert_eq!(r.len(), 1);
         assert_eq!(to_minutes(r[0].start), 0);
         assert_eq!(to_minutes(r[0].end), to_minutes((24, 00)));
@@ -403,7 +1042,7 @@ mod tests {
     #[test]
     fn fill_start() {
         let mut r = vec![TimeRange::new((12, 0), (24, 0), Color::DarkGray)];
-        fill_gaps(&mut r, &Default::default());
+        fill_gaps(&mut r, Color::DarkGray);
         assert_eq!(r.len(), 2);
         assert_eq!(to_minutes(r[0].start), 0);
         assert_eq!(to_minutes(r[0].end), to_minutes((12, 00)));
@@ -414,7 +1053,7 @@ mod tests {
     #[test]
     fn fill_end() {
         let mut r = vec![TimeRange::new((0, 0), (12, 0), Color::DarkGray)];
-        fill_gaps(&mut r, &Default::default());
+        fill_gaps(&mut r, Color::DarkGray);
         assert_eq!(r.len(), 2);
         assert_eq!(to_minutes(r[0].start), 0);
         assert_eq!(to_minutes(r[0].end), to_minutes((12, 00)));
@@ -422,13 +1061,26 @@ mod tests {
         assert_eq!(to_minutes(r[1].end), to_minutes((24, 00)));
     }
 
+    #[test]
+    fn fill_several_gaps() {
+        let mut r = vec![
+            TimeRange::new((1, 0), (2, 0), Color::White),
+            TimeRange::new((3, 0), (4, 0), Color::White),
+            TimeRange::new((5, 0), (6, 0), Color::White),
+        ];
+        fill_gaps(&mut r, Color::DarkGray);
+        let gaps: Vec<bool> = r.iter().map(|r| r.gap).collect();
+        assert_eq!(gaps, [true, false, true, false, true, false, true]);
+        assert_eq!(r[6].start, (6, 0));
+    }
+
     #[test]
     fn fill_in_between() {
         let mut r = vec![
             TimeRange::new((0, 0), (10, 0), Color::DarkGray),
             TimeRange::new((18, 0), (24, 0), Color::DarkGray),
         ];
-        fill_gaps(&mut r, &Default::default());
+        fill_gaps(&mut r, Color::DarkGray);
         assert_eq!(r.len(), 3);
         assert_eq!(to_minutes(r[0].start), 0);
         assert_eq!(to_minutes(r[0].end), to_minutes((10, 00)));
@@ -444,7 +1096,7 @@ mod tests {
             TimeRange::new((0, 0), (12, 0), Color::DarkGray),
             TimeRange::new((12, 0), (18, 0), Color::DarkGray),
         ];
-        fill_gaps(&mut r, &Default::default());
+        fill_gaps(&mut r, Color::DarkGray);
         assert_eq!(r.len(), 3);
         assert_eq!(to_minutes(r[0].start), 0);
         assert_eq!(to_minutes(r[0].end), to_minutes((12, 00)));
@@ -453,4 +1105,555 @@ mod tests {
         assert_eq!(to_minutes(r[2].start), to_minutes((18, 00)));
         assert_eq!(to_minutes(r[2].end), to_minutes((24, 00)));
     }
+
+    // notice
+
+    #[test]
+    fn error_notice_lists_causes() {
+        let err = eyre::eyre!("line:1 col:2").wrap_err("Failed to parse config file");
+        let notice = Notice::error(&err);
+        assert_eq!(notice.text, "Failed to parse config file\nline:1 col:2");
+        assert!(!notice.expired(chrono::Utc::now() + chrono::Duration::days(1)));
+    }
+
+    #[test]
+    fn theme_switch_recolors_gaps() {
+        let mut config: Config = ron::from_str(
+            r#"(theme: "light", tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 0), (18, 0), Color::Yellow)];
+        let mut app = App::new(config);
+        let light = theme::preset("light").unwrap();
+        assert_eq!(app.theme_name(), "light");
+        assert_eq!(app.config.tracks[0].ranges[0].color, light.base);
+        assert_eq!(app.config.tracks[0].ranges[1].color, Color::Yellow);
+        assert_eq!(app.next_theme(), "solarized");
+        let colors: Vec<Color> = app.config.tracks[0].ranges.iter().map(|r| r.color).collect();
+        let base = theme::preset("solarized").unwrap().base;
+        assert_eq!(colors, [base, Color::Yellow, base]);
+        assert!(!app.set_theme("nope"));
+        assert_eq!(app.theme_name(), "solarized");
+    }
+
+    #[test]
+    fn colors_downsampled_on_render() {
+        let mut config: Config =
+            ron::from_str(r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#).unwrap();
+        config.tracks[0].ranges = vec![
+            TimeRange::new((0, 0), (12, 0), Color::Rgb(250, 200, 0)),
+            TimeRange::new((12, 0), (24, 0), Color::Blue),
+        ];
+        let mut app = App::new(config);
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(60, 3)).unwrap();
+        app.update_layout(terminal.size().unwrap());
+        let row = |app: &mut App, terminal: &mut Terminal<_>| {
+            let frame = app.render(terminal).unwrap();
+            frame.buffer.content[60..120].to_vec()
+        };
+
+        app.set_color_support(ColorSupport::Ansi256);
+        let cells = row(&mut app, &mut terminal);
+        assert!(cells.iter().any(|c| c.fg == Color::Indexed(220)));
+        assert!(cells.iter().all(|c| !matches!(c.fg, Color::Rgb(..))));
+
+        // both ranges are drawn with glyphs of their own
+        app.set_color_support(ColorSupport::Mono);
+        let cells = row(&mut app, &mut terminal);
+        assert!(cells.iter().all(|c| c.fg == Color::Reset && c.bg == Color::Reset));
+        assert!(cells.iter().any(|c| c.symbol == "━"));
+        assert!(cells.iter().any(|c| c.symbol == "═"));
+    }
+
+    #[test]
+    fn range_glyphs_and_block_bars() {
+        let mut config: Config =
+            ron::from_str(r#"(bar: Block, tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#)
+                .unwrap();
+        let mut shaded = TimeRange::new((0, 0), (12, 0), Color::Yellow);
+        shaded.glyph = Some(theme::BarGlyph::LightShade);
+        config.tracks[0].ranges = vec![shaded, TimeRange::new((12, 0), (24, 0), Color::Blue)];
+        let mut app = App::new(config);
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(60, 3)).unwrap();
+        app.update_layout(terminal.size().unwrap());
+        let frame = app.render(&mut terminal).unwrap();
+        let symbols: Vec<&str> =
+            frame.buffer.content[60..120].iter().map(|c| c.symbol.as_str()).collect();
+        assert!(symbols.contains(&"░"));
+        assert!(symbols.contains(&"█"));
+        assert!(!symbols.contains(&"━"));
+    }
+
+    #[test]
+    fn hires_borders_within_cells() {
+        let mut config: Config = ron::from_str(
+            r#"(hires: true, tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![
+            TimeRange::new((0, 0), (12, 0), Color::Yellow),
+            TimeRange::new((12, 0), (24, 0), Color::Blue),
+        ];
+        let mut app = App::new(config);
+        // 22 cells a day on both sides of the clock, so 06:00 and 12:00 fall on the cell middles
+        let aux = &mut app.lines[0];
+        aux.chart = Rect::new(0, 0, 51, 1);
+        aux.clock = Rect::new(22, 0, 7, 1);
+        aux.bars.1.x = 29;
+        app.visible_lines = 1;
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 1, 1, 6, 0, 0).unwrap());
+
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(51, 1)).unwrap();
+        let frame = terminal
+            .draw(|f| ui::render_hires_bars(f, &app.lines[0], &app.config.tracks[0], &app.theme))
+            .unwrap();
+        let cell = |x| frame.buffer.get(x, 0).clone();
+        // 00:00 is in the middle of the first left cell
+        assert_eq!((app.lines[0].bars.0.x, app.lines[0].bars.0.width), (16, 6));
+        assert_eq!(cell(16).symbol, "▌");
+        assert!(cell(16).modifier.contains(tui::style::Modifier::REVERSED));
+        assert_eq!((cell(17).symbol.as_str(), cell(17).fg), ("█", Color::Yellow));
+        // 12:00 is 5.5 cells to the right of the clock
+        assert_eq!(
+            (cell(34).symbol.as_str(), cell(34).fg, cell(34).bg),
+            ("▌", Color::Yellow, Color::Blue)
+        );
+        assert_eq!((cell(35).symbol.as_str(), cell(35).fg), ("█", Color::Blue));
+    }
+
+    #[test]
+    fn daylight_ranges_follow_the_date() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0), location: (51.5074, -0.1278))])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 0), (17, 0), Color::Blue)];
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        let winter = chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 12, 21, 23, 0, 0).unwrap();
+        app.tick(winter);
+        let kinds = |app: &App| -> Vec<Option<solar::Daylight>> {
+            app.config.tracks[0].ranges.iter().map(|r| r.daylight).collect()
+        };
+        let (night, twilight) = (Some(solar::Daylight::Night), Some(solar::Daylight::Twilight));
+        // the evening twilight is over before the workday ends
+        assert_eq!(kinds(&app), [night, twilight, None, None, night]);
+        let ranges = &app.config.tracks[0].ranges;
+        assert_eq!(ranges[0].color, app.theme.night);
+        // the night goes on past midnight up to the dawn around 07:25
+        let left = next_transition(to_minutes((23, 0)), ranges).unwrap();
+        assert!((to_minutes((8, 20))..to_minutes((8, 30))).contains(&left), "{}", left);
+        let table = query::table(&app.config.tracks, winter);
+        assert_eq!(table, ["a  Mon 23:00  -"]);
+
+        // short summer night, the twilight takes the evening after the workday
+        let summer = chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 6, 21, 12, 0, 0).unwrap();
+        app.tick(summer);
+        assert_eq!(kinds(&app), [night, twilight, None, None, None, twilight, night]);
+        app.set_theme("light");
+        assert_eq!(app.config.tracks[0].ranges[0].color, app.theme.night);
+    }
+
+    #[test]
+    fn override_ranges_on_their_date() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [(name: "NYSE", shortname: "NY", offset: (-5, 0),
+                overrides: [(date: "2026-11-27", ranges: [(start: (9, 30), end: (13, 0), color: Red)])])])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 30), (16, 0), Color::Blue)];
+        config.tracks[0].overrides[0].ranges = vec![TimeRange::new((9, 30), (13, 0), Color::Red)];
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        let at = |day, hour| chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, day, hour, 0, 0);
+        let ends = |app: &App| -> Vec<(u16, u16)> {
+            app.config.tracks[0].ranges.iter().filter(|r| !r.gap).map(|r| r.end).collect()
+        };
+        // 14:00 of the day before and of the day after Thanksgiving in New York
+        app.tick(at(26, 19).unwrap());
+        assert_eq!(ends(&app), [(16, 0)]);
+        app.tick(at(27, 19).unwrap());
+        assert_eq!(ends(&app), [(13, 0)]);
+        assert_eq!(app.lines[0].current_range, 2);
+        // the early close of the day is taken by the queries as well
+        assert_eq!(
+            app.query("10:00 NYSE", at(28, 3).unwrap()).unwrap(),
+            ["NYSE  Fri 10:00  09:30-13:00"]
+        );
+        app.tick(at(28, 19).unwrap());
+        assert_eq!(ends(&app), [(16, 0)]);
+    }
+
+    #[test]
+    fn overlap_of_the_date() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [(name: "London", shortname: "LDN", offset: (0, 0)),
+                (name: "NYSE", shortname: "NY", offset: (-5, 0),
+                overrides: [(date: "2026-11-27", ranges: [(start: (9, 30), end: (11, 0), color: White)])])])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 0), (17, 0), Color::White)];
+        config.tracks[1].ranges = vec![TimeRange::new((9, 30), (16, 0), Color::White)];
+        config.tracks[1].overrides[0].ranges = vec![TimeRange::new((9, 30), (11, 0), Color::White)];
+        let mut app = App::new(config);
+        let date = |day| NaiveDate::from_ymd_opt(2026, 11, day);
+        assert_eq!(app.overlap(&[], None, date(26)).unwrap()[0], "UTC 14:30-17:00  2h 30m");
+        assert_eq!(app.overlap(&[], None, date(27)).unwrap()[0], "UTC 14:30-16:00  1h 30m");
+
+        // the band follows the tracks to their next day
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        let at = |day, hour| chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, day, hour, 0, 0);
+        app.tick(at(26, 19).unwrap());
+        assert_eq!(app.toggle_overlap(), Some(1));
+        let windows = |app: &App| app.band.as_ref().map(|(_, windows)| windows.clone());
+        assert_eq!(windows(&app), Some(vec![(to_minutes((14, 30)), to_minutes((17, 0)))]));
+        app.tick(at(27, 19).unwrap());
+        assert_eq!(windows(&app), Some(vec![(to_minutes((14, 30)), to_minutes((16, 0)))]));
+    }
+
+    #[test]
+    fn schedule_of_the_next_days() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [(name: "NYSE", shortname: "NY", offset: (-5, 0),
+                overrides: [(date: "2026-11-26", ranges: [])])])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 30), (16, 0), Color::Blue)];
+        let mut app = App::new(config);
+        // 23:00 of November 25th in New York
+        let from = chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 26, 4, 0, 0).unwrap();
+        let entries = app.schedule(from, 3);
+        let uids: Vec<&str> = entries.iter().map(|e| e.uid.as_str()).collect();
+        assert_eq!(uids, ["ny-20261125-0930@mvtime", "ny-20261127-0930@mvtime"]);
+        let start = chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 27, 14, 30, 0).unwrap();
+        assert_eq!(
+            (entries[1].start, entries[1].end),
+            (start, start + chrono::Duration::minutes(390))
+        );
+        assert_eq!(entries[1].description, "NY 09:30-16:00 UTC-05:00");
+    }
+
+    #[test]
+    fn calendar_events_over_ranges() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [(name: "a", shortname: "a", offset: (1, 0), calendar: (file: "a.ics"))])"#,
+        )
+        .unwrap();
+        let calendar = config.tracks[0].calendar.as_mut().unwrap();
+        calendar.events = ics::parse(
+            "BEGIN:VEVENT\nDTSTART:20261102T090000Z\nDURATION:PT30M\nRRULE:FREQ=DAILY\nEND:VEVENT",
+        )
+        .unwrap();
+        calendar.colors = vec![Color::Red];
+        config.tracks[0].ranges = vec![TimeRange::new((9, 0), (17, 0), Color::Blue)];
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 5, 12, 0, 0).unwrap());
+        let ranges: Vec<_> = app.config.tracks[0]
+            .ranges
+            .iter()
+            .filter(|r| !r.gap)
+            .map(|r| (r.start, r.end, r.color))
+            .collect();
+        // 09:00 UTC is 10:00 on the track
+        assert_eq!(
+            ranges,
+            [
+                ((9, 0), (10, 0), Color::Blue),
+                ((10, 0), (10, 30), Color::Red),
+                ((10, 30), (17, 0), Color::Blue)
+            ]
+        );
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 1, 12, 0, 0).unwrap());
+        assert_eq!(app.config.tracks[0].ranges.len(), 3);
+    }
+
+    #[test]
+    fn progress_through_current_range() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0), time_label: (progress: true))])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![
+            TimeRange::new((0, 0), (9, 0), Color::Yellow),
+            TimeRange::new((9, 0), (17, 0), Color::Blue),
+            TimeRange::new((17, 0), (24, 0), Color::Yellow),
+        ];
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        assert_eq!(app.lines[0].clock.width, 11);
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 1, 1, 14, 3, 0).unwrap());
+        assert_eq!(app.lines[0].current_range, 1);
+        assert!((app.lines[0].progress - 0.63).abs() < 0.01);
+
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(80, 10)).unwrap();
+        let frame = terminal
+            .draw(|f| {
+                let line = &app.lines[0];
+                ui::render_clock(f, 0, line, &app.config.tracks[0], &app.theme, false)
+            })
+            .unwrap();
+        let clock = app.lines[0].clock;
+        let text: String =
+            (clock.x..clock.right()).map(|x| frame.buffer.get(x, clock.y).symbol.clone()).collect();
+        assert_eq!(text, " 14:03 63% ");
+    }
+
+    #[test]
+    fn next_transition_skips_midnight_gaps() {
+        let mut ranges = vec![TimeRange::new((9, 0), (17, 0), Color::Blue)];
+        fill_gaps(&mut ranges, Color::DarkGray);
+        assert_eq!(next_transition(to_minutes((10, 0)), &ranges), Some(7 * 60));
+        // 20:00 -> 09:00 the next day
+        assert_eq!(next_transition(to_minutes((20, 0)), &ranges), Some(13 * 60));
+        let mut ranges = vec![TimeRange::new((0, 0), (17, 0), Color::Blue)];
+        fill_gaps(&mut ranges, Color::DarkGray);
+        assert_eq!(next_transition(to_minutes((20, 0)), &ranges), Some(4 * 60));
+        let mut ranges = vec![];
+        fill_gaps(&mut ranges, Color::DarkGray);
+        assert_eq!(next_transition(0, &ranges), None);
+    }
+
+    #[test]
+    fn selected_track_detail() {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [
+                (name: "Tokyo", shortname: "TYO", offset: (9, 0)),
+                (name: "London", shortname: "LDN", offset: (0, 0)),
+            ])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 0), (17, 0), Color::Blue)];
+        let mut app = App::new(config);
+        app.reference = chrono::Duration::hours(2);
+        app.update_layout(Rect::new(0, 0, 80, 20));
+
+        app.toggle_detail();
+        assert!(!app.detail_open(), "nothing to show without a selection");
+        app.select_prev();
+        assert_eq!(app.selected(), Some(1));
+        app.select_next();
+        assert_eq!(app.selected(), Some(1));
+        app.select_prev();
+        app.select_prev();
+        assert_eq!(app.selected(), Some(0));
+        app.select_next();
+        app.toggle_detail();
+        assert!(app.detail_open());
+
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 1, 1, 5, 3, 0).unwrap();
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(80, 20)).unwrap();
+        let frame = terminal
+            .draw(|f| {
+                // tracks are sorted by offset, Tokyo is the second one
+                let track = &app.config.tracks[app.selected().unwrap()];
+                ui::render_detail(f, track, now, app.reference, &app.theme)
+            })
+            .unwrap();
+        let rows: Vec<String> = (0..20)
+            .map(|y| (0..80).map(|x| frame.buffer.get(x, y).symbol.clone()).collect())
+            .collect();
+        let has = |text: &str| rows.iter().any(|row| row.contains(text));
+        assert!(has(" Tokyo "));
+        assert!(has("Offset     UTC+09:00"));
+        assert!(has("Local      Thu 2026-01-01 14:03"));
+        assert!(has("Next       17:00, in 2h 57m"));
+        assert!(has("Ranges     local         UTC+02:00"));
+        assert!(has("09:00-17:00   02:00-10:00"));
+    }
+
+    #[test]
+    fn selection_scrolls_and_toggles() {
+        let config: Config = ron::from_str(
+            r#"(keys: {seconds: ["S"]}, tracks: [
+                (name: "first", shortname: "a", offset: (0, 0)),
+                (name: "second", shortname: "b", offset: (1, 0)),
+                (name: "third", shortname: "c", offset: (2, 0)),
+            ])"#,
+        )
+        .unwrap();
+        let mut app = App::new(config);
+        // two lines between the margins
+        app.update_layout(Rect::new(0, 0, 80, 4));
+        assert_eq!((app.visible_lines, app.scroll), (2, 0));
+        app.select_next();
+        app.select_next();
+        app.select_next();
+        assert_eq!((app.selected(), app.scroll), (Some(2), 1));
+        app.scroll_down();
+        assert_eq!(app.scroll, 1);
+        app.select_prev();
+        app.select_prev();
+        assert_eq!((app.selected(), app.scroll), (Some(0), 0));
+
+        let key = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('S'),
+            crossterm::event::KeyModifiers::SHIFT,
+        );
+        assert_eq!(app.keys().action(key), Some(keys::Action::Seconds));
+        app.toggle_seconds();
+        assert_eq!(app.lines[0].clock.width, 10);
+        app.toggle_seconds();
+        assert_eq!(app.lines[0].clock.width, 7);
+
+        assert_eq!(app.lines[0].title_text, "first");
+        app.toggle_names();
+        assert_eq!(app.lines[0].title_text, "a");
+        app.toggle_names();
+        assert_eq!(app.lines[0].title_text, "first");
+    }
+
+    #[test]
+    fn prompt_inspects_queried_time() {
+        let config: Config = ron::from_str(
+            r#"(tracks: [
+                (name: "London", shortname: "LDN", offset: (0, 0)),
+                (name: "Tokyo", shortname: "TYO", offset: (9, 0)),
+            ])"#,
+        )
+        .unwrap();
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        app.open_prompt();
+        "14:00 Paris".chars().for_each(|c| app.prompt_push(c));
+        app.submit_prompt(Utc::now());
+        assert!(app.prompt_open() && !app.inspecting());
+        assert!(app.prompt.as_ref().unwrap().1.as_ref().unwrap().starts_with("unknown track"));
+        (0..5).for_each(|_| app.prompt_pop());
+        "ldn".chars().for_each(|c| app.prompt_push(c));
+        app.submit_prompt(Utc::now());
+        assert!(!app.prompt_open() && app.inspecting());
+
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(80, 10)).unwrap();
+        app.render(&mut terminal).unwrap();
+        assert_eq!(app.lines[0].local_time, (14, 0));
+        assert_eq!(app.lines[1].local_time, (23, 0));
+        app.back_to_now();
+        assert!(!app.inspecting());
+    }
+
+    #[test]
+    fn span_days_and_separators() {
+        let mut config: Config = ron::from_str(
+            r#"(span: "48h", tracks: [(name: "a", shortname: "a", offset: (0, 0),
+                overrides: [(date: "2026-11-27", ranges: [(start: (9, 0), end: (13, 0), color: Red)])])])"#,
+        )
+        .unwrap();
+        config.tracks[0].ranges = vec![TimeRange::new((9, 0), (17, 0), Color::Blue)];
+        config.tracks[0].overrides[0].ranges = vec![TimeRange::new((9, 0), (13, 0), Color::Red)];
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        // Thursday noon, the span takes Friday as well
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 26, 12, 0, 0).unwrap());
+        let line = &app.lines[0];
+        assert_eq!(line.window, (0, 2 * MINUTES_PER_DAY));
+        assert_eq!(line.days.len(), 2);
+        assert_eq!(line.days[1][1].end, (13, 0));
+        let (day, ranges, minute) = line.day_at(&app.config.tracks[0], 1440. + 600.);
+        assert_eq!((day, ranges[1].color, minute), (1, Color::Red, 600.));
+
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(80, 10)).unwrap();
+        let frame = terminal
+            .draw(|f| ui::render_bars(f, line, &app.config.tracks[0], &app.theme, false))
+            .unwrap();
+        let (lbar, rbar) = line.bars;
+        let cells: Vec<String> = (lbar.left()..lbar.right())
+            .chain(rbar.left()..rbar.right())
+            .map(|x| frame.buffer.get(x, lbar.y).symbol.clone())
+            .collect();
+        // a quarter of the bar is before the clock, the midnight is in the middle
+        let midnight = cells.iter().position(|c| c == "│").unwrap();
+        assert_eq!(cells.iter().filter(|c| *c == "│").count(), 1);
+        assert!(midnight.abs_diff(cells.len() / 2) <= 1);
+        assert!(usize::from(lbar.width).abs_diff(cells.len() / 4) <= 1);
+
+        // the week from Monday, and back to the day
+        assert_eq!(app.zoom(false), Span::Week);
+        assert_eq!(app.zoom(false), Span::Week);
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 26, 12, 0, 0).unwrap());
+        assert_eq!(app.lines[0].window, (-3 * 1440, 7 * MINUTES_PER_DAY));
+        assert_eq!(app.lines[0].days.len(), 7);
+        assert_eq!(app.zoom(true), Span::TwoDays);
+        assert_eq!(app.zoom(true), Span::Day);
+        app.tick(chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 11, 26, 12, 0, 0).unwrap());
+        assert!(app.lines[0].days.is_empty());
+        assert_eq!(app.zoom(true), Span::HalfDay);
+        assert_eq!(Span::HalfDay.window(12, chrono::Weekday::Mon), (720, 720));
+    }
+
+    #[test]
+    fn overlap_band_and_table() {
+        let mut config: Config = ron::from_str(
+            r#"(overlap: (tracks: ["LDN", "msk"]), tracks: [
+                (name: "London", shortname: "LDN", offset: (0, 0)),
+                (name: "Moscow", shortname: "MSK", offset: (3, 0)),
+                (name: "Tokyo", shortname: "TYO", offset: (9, 0)),
+            ])"#,
+        )
+        .unwrap();
+        for track in &mut config.tracks {
+            track.ranges = vec![TimeRange::new((9, 0), (18, 0), Color::White)];
+        }
+        let mut app = App::new(config);
+        assert_eq!(
+            app.overlap(&["london".to_string(), "moscow".to_string()], Some("White"), None)
+                .unwrap(),
+            ["UTC 09:00-15:00  6h 00m", "  London  09:00-15:00", "  Moscow  12:00-18:00"]
+        );
+        assert!(app.overlap(&[], Some("main"), None).unwrap_err().starts_with("unknown color"));
+        assert!(app.overlap(&[], None, None).unwrap().is_empty());
+
+        app.update_layout(Rect::new(0, 0, 80, 10));
+        assert_eq!(app.toggle_overlap(), Some(1));
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(80, 10)).unwrap();
+        let frame = app.render(&mut terminal).unwrap();
+        let reversed = |i: usize| {
+            let (lbar, rbar) = app.lines[i].bars;
+            (lbar.left()..lbar.right())
+                .chain(rbar.left()..rbar.right())
+                .filter(|x| {
+                    frame.buffer.get(*x, lbar.y).modifier.contains(tui::style::Modifier::REVERSED)
+                })
+                .count()
+        };
+        // a quarter of the day on London and Moscow rows, nothing on Tokyo one
+        let width = usize::from(app.lines[0].bars.0.width + app.lines[0].bars.1.width);
+        assert!((reversed(0) as f64 / width as f64 - 0.25).abs() < 0.05);
+        assert!(reversed(0).abs_diff(reversed(1)) <= 1, "cells are rounded on each row");
+        assert_eq!(reversed(2), 0);
+        assert_eq!(app.toggle_overlap(), None);
+    }
+
+    #[test]
+    fn help_lists_keys_and_status() {
+        let mut config: Config =
+            ron::from_str(r#"(tracks: [(name: "a", shortname: "a", offset: (0, 0))])"#).unwrap();
+        config.files = vec![PathBuf::from("/tmp/mvtime.ron")];
+        let mut app = App::new(config);
+        app.update_layout(Rect::new(0, 0, 80, 30));
+        app.toggle_help();
+        app.set_reloaded(Utc::now(), Some("Bad offset".to_string()));
+
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(80, 30)).unwrap();
+        let frame = app.render(&mut terminal).unwrap();
+        let rows: Vec<String> = (0..30)
+            .map(|y| (0..80).map(|x| frame.buffer.get(x, y).symbol.clone()).collect())
+            .collect();
+        let has = |text: &str| rows.iter().any(|row| row.contains(text));
+        assert!(has("Help, any key to close"));
+        assert!(has("j, Down    select the next track"));
+        assert!(has("q, Ctrl-c  quit"));
+        assert!(has("Esc        close the help/details"));
+        assert!(has("Config     /tmp/mvtime.ron"));
+        assert!(has(": Bad offset"));
+        assert!(has(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn info_notice_expires() {
+        let now = chrono::Utc::now();
+        let notice = Notice::info("Config reloaded", now);
+        assert!(!notice.expired(now));
+        assert!(notice.expired(now + chrono::Duration::seconds(5)));
+    }
 }
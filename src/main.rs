@@ -1,26 +1,31 @@
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{self, Event, KeyCode, KeyModifiers},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
 };
 use eyre::{Result, WrapErr};
-use mvtime::{config, App};
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use mvtime::{
+    check,
+    color::ColorSupport,
+    config::{self, Span},
+    ics, init,
+    keys::Action,
+    print,
+    ui::Notice,
+    watch::ConfigWatcher,
+    App,
+};
 use std::{
-    io::{self, Stdout},
-    path::PathBuf,
-    sync::mpsc::Receiver,
+    io::{self, IsTerminal, Stdout, Write},
+    path::{Path, PathBuf},
     time::{Duration, SystemTime},
 };
 use tui::{backend::CrosstermBackend, layout::Rect, Terminal};
 
 const TICK_RATE: i64 = 1000;
 
-fn is_exit_key(key: KeyEvent) -> bool {
-    const CTRL_C: KeyEvent = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
-    key.code.eq(&KeyCode::Char('q')) || key.code.eq(&KeyCode::Esc) || key.eq(&CTRL_C)
-}
-
 fn poll(ms: u64) -> Result<Option<Event>> {
     if event::poll(Duration::from_millis(ms)).wrap_err("Failed to poll for new terminal events")? {
         let e = event::read().wrap_err("Failed to read new terminal events")?;
@@ -30,12 +35,10 @@ fn poll(ms: u64) -> Result<Option<Event>> {
     }
 }
 
-fn init(one_time: bool) -> Result<Terminal<CrosstermBackend<Stdout>>> {
+fn init() -> Result<Terminal<CrosstermBackend<Stdout>>> {
     let mut stdout = io::stdout();
     enable_raw_mode().wrap_err("Switching to raw terminal mode failed")?;
-    if !one_time {
-        execute!(stdout, EnterAlternateScreen).wrap_err("Alternate screen switching failed")?;
-    }
+    execute!(stdout, EnterAlternateScreen).wrap_err("Alternate screen switching failed")?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).wrap_err("Terminal backend initialization failed")?;
     terminal.clear()?;
@@ -43,32 +46,98 @@ fn init(one_time: bool) -> Result<Terminal<CrosstermBackend<Stdout>>> {
     Ok(terminal)
 }
 
-fn start_watcher(
-    path: PathBuf,
-) -> Result<(notify::INotifyWatcher, Receiver<Result<notify::Event, notify::Error>>)> {
-    let (tx, rx) = std::sync::mpsc::channel();
-    let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())?;
-    watcher.watch(path.as_ref(), RecursiveMode::NonRecursive)?;
-    Ok((watcher, rx))
+// terminal width for one-time output, stdout might be a pipe so fallback to $COLUMNS and then to 80
+fn output_width() -> u16 {
+    terminal::size()
+        .map(|(w, _)| w)
+        .ok()
+        .or_else(|| std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()))
+        .filter(|w| *w > 0)
+        .unwrap_or(80)
 }
 
-fn should_reload(rx: &Receiver<Result<notify::Event, notify::Error>>) -> bool {
-    if let Ok(e) = rx.try_recv() {
-        e.is_ok() && e.unwrap().kind.is_modify()
-    } else {
-        false
-    }
+fn finalize(mut terminal: Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen,)?;
+    terminal.show_cursor()?;
+    Ok(())
 }
 
-fn finalize(mut terminal: Terminal<CrosstermBackend<Stdout>>, one_time: bool) -> Result<()> {
-    disable_raw_mode()?;
-    if !one_time {
-        execute!(terminal.backend_mut(), LeaveAlternateScreen,)?;
+// print config files found in the search directories
+fn list_configs() -> Result<()> {
+    for dir in config::search_dirs()? {
+        println!("{}", dir.display());
+        let configs = config::list_configs(&dir);
+        if configs.is_empty() {
+            println!("  -");
+        }
+        for path in configs {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            println!("  {:<16} {}", name, path.display());
+        }
     }
-    terminal.show_cursor()?;
     Ok(())
 }
 
+// configs given in the command line, followed by the ones found in the OS config dir if `--all` is set.
+// Files of the dir included by another one or with no tracks at all are fragments, not profiles.
+fn profiles(matches: &clap::ArgMatches) -> Result<Vec<PathBuf>> {
+    let all = matches.is_present("all");
+    let mut paths = vec![];
+    if !all || matches.occurrences_of("config") > 0 {
+        for name in matches.values_of("config").into_iter().flatten() {
+            paths.push(config::find_config(name).wrap_err("Can't find a config file")?);
+        }
+    }
+    if all {
+        let dir = config::config_dir().ok_or_else(|| eyre::eyre!("No OS config directory"))?;
+        let mut found = vec![];
+        let mut included = vec![];
+        for path in config::list_configs(&dir) {
+            let path = path.canonicalize()?;
+            // broken ones are kept, so their errors are shown
+            match config::read_configs(path.clone()) {
+                Ok(config) if config.tracks.is_empty() => continue,
+                Ok(config) => {
+                    let files = config.files.iter().skip(1);
+                    included.extend(files.filter_map(|file| file.canonicalize().ok()));
+                }
+                Err(_) => {}
+            }
+            found.push(path);
+        }
+        for path in found {
+            if !paths.contains(&path) && !included.contains(&path) {
+                paths.push(path);
+            }
+        }
+        if paths.is_empty() {
+            eyre::bail!("No configs found in {:?}", dir);
+        }
+    }
+    Ok(paths)
+}
+
+// theme picked with 't' is kept over reloads and config switches, if the config has it
+fn load_app(
+    path: &Path,
+    screen: Rect,
+    theme: Option<&str>,
+    span: Option<Span>,
+    color_support: ColorSupport,
+) -> Result<App> {
+    let mut app = App::new(config::load_config(path.to_path_buf())?);
+    app.set_color_support(color_support);
+    if let Some(theme) = theme {
+        app.set_theme(theme);
+    }
+    if let Some(span) = span {
+        app.set_span(span);
+    }
+    app.update_layout(screen);
+    Ok(app)
+}
+
 fn main() -> Result<()> {
     #[cfg(not(debug_assertions))]
     simple_eyre::install()?;
@@ -78,32 +147,280 @@ fn main() -> Result<()> {
                 .long("live")
                 .takes_value(false)
                 .help("Run app in live mode"),
+        clap::Arg::new("all")
+                .short('a')
+                .long("all")
+                .takes_value(false)
+                .help("Use all the configs from the '<OS_CONFIGS_LOCATION>/mvtime' directory"),
+        clap::Arg::new("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(ColorSupport::NAMES)
+                .default_value("auto")
+                .help("Colors to use, 'auto' picks them by the NO_COLOR, COLORTERM and TERM variables, \
+no colors are printed to a pipe in this case"),
+        clap::Arg::new("list-configs")
+                .long("list-configs")
+                .takes_value(false)
+                .help("List config files found in the './' and '<OS_CONFIGS_LOCATION>/mvtime' directories"),
         clap::Arg::new("config")
-        .max_occurrences(1)
+        .multiple_values(true)
         .default_value("default")
-        .help("Path to the config file. Might be set as a path, or a file name without '.ron' extension, \
-in this case it will be searched for in the './' and '<OS_CONFIGS_LOCATION>/mvtime' directories")];
-    let matches =
-        clap::Command::new("mvtime").about("Multiverse CLI time tracker").args(args).get_matches();
+        .help("Paths to the config files. Might be set as a path, or a file name without '.ron|.toml|.yaml|.json' extension, \
+in this case it will be searched for in the './' and '<OS_CONFIGS_LOCATION>/mvtime' directories. \
+In live mode 'Tab' switches between configs")];
+    let check_cmd = clap::Command::new("check")
+        .about("Check config files and report all the problems found, exits with non-zero code on errors")
+        .arg(clap::Arg::new("config").multiple_values(true).default_value("default"));
+    let init_cmd = clap::Command::new("init")
+        .about("Write a starter config for the local time zone into the '<OS_CONFIGS_LOCATION>/mvtime' directory")
+        .arg(
+            clap::Arg::new("zone")
+                .long("zone")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .help("Additional time zone track, e.g. 'Europe/London'"),
+        )
+        .arg(
+            clap::Arg::new("name")
+                .long("name")
+                .default_value("default")
+                .help("Config file name, its extension is replaced with '.ron'"),
+        )
+        .arg(clap::Arg::new("force").long("force").help("Overwrite existing config file"));
+    let convert_cmd = clap::Command::new("convert")
+        .about("Convert config file to another format, picked by the output file extension: ron, toml, yaml, json. \
+Given a time query, e.g. --at '14:00 London', print the local time and range of every track at that time instead")
+        .arg(
+            clap::Arg::new("input")
+                .required_unless_present("at")
+                .help("Config file to convert"),
+        )
+        .arg(clap::Arg::new("output").help("Path to the converted config file"))
+        .arg(clap::Arg::new("force").long("force").help("Overwrite existing output file"))
+        .arg(
+            clap::Arg::new("at")
+                .long("at")
+                .takes_value(true)
+                .conflicts_with_all(&["input", "output", "force"])
+                .help("Time query, e.g. '14:00 London'"),
+        )
+        .arg(
+            clap::Arg::new("config")
+                .long("config")
+                .takes_value(true)
+                .default_value("default")
+                .help("Config with the tracks for the time query"),
+        );
+    let overlap_cmd = clap::Command::new("overlap")
+        .about("Print the windows of the day when all the tracks are within their ranges, in UTC and the tracks' time")
+        .arg(
+            clap::Arg::new("tracks")
+                .long("tracks")
+                .takes_value(true)
+                .use_value_delimiter(true)
+                .help("Comma separated track names or shortnames, all the tracks by default"),
+        )
+        .arg(
+            clap::Arg::new("range")
+                .long("range")
+                .takes_value(true)
+                .help("Color or palette name of the ranges, e.g. 'White', any range by default"),
+        )
+        .arg(
+            clap::Arg::new("date")
+                .long("date")
+                .takes_value(true)
+                .help("Day of the tracks' ranges as YYYY-MM-DD, today by default"),
+        )
+        .arg(clap::Arg::new("config").multiple_values(true).default_value("default"));
+    let export_cmd = clap::Command::new("export-ics")
+        .about("Write the track ranges of the next days as iCalendar events in UTC, one event per range and day, \
+date overrides and calendars included")
+        .arg(clap::Arg::new("config").default_value("default").help("Config with the tracks to export"))
+        .arg(
+            clap::Arg::new("days")
+                .long("days")
+                .takes_value(true)
+                .default_value("30")
+                .help("Number of days to export, starting with today"),
+        )
+        .arg(
+            clap::Arg::new("output")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .help("Path to the .ics file, the calendar is printed if not set"),
+        )
+        .arg(clap::Arg::new("force").long("force").help("Overwrite existing output file"));
+    let matches = clap::Command::new("mvtime")
+        .about("Multiverse CLI time tracker")
+        .args(args)
+        .subcommand(check_cmd)
+        .subcommand(init_cmd)
+        .subcommand(convert_cmd)
+        .subcommand(overlap_cmd)
+        .subcommand(export_cmd)
+        .args_conflicts_with_subcommands(true)
+        .get_matches();
 
-    let one_time = !matches.is_present("mode");
-    let config = config::find_config(matches.value_of("config").unwrap())
-        .wrap_err("Can't find a config file")?;
+    if matches.is_present("list-configs") {
+        return list_configs();
+    }
 
-    // Load/Parse config file
-    let tracks_cfg = config::load_config(config.clone())?;
+    if let Some(("init", matches)) = matches.subcommand() {
+        let zones: Vec<String> =
+            matches.values_of("zone").into_iter().flatten().map(String::from).collect();
+        let name = matches.value_of("name").unwrap();
+        let path = init::run(name, &zones, matches.is_present("force"))?;
+        println!("Config written to {}", path.display());
+        return Ok(());
+    }
 
-    let mut terminal = init(one_time)?;
-    let mut app = App::new(tracks_cfg);
-    terminal.size().map(|rect| app.update_layout(rect))?;
+    if let Some(("convert", matches)) = matches.subcommand() {
+        let query = |query: &str| -> Result<()> {
+            let path = config::find_config(matches.value_of("config").unwrap())
+                .wrap_err("Can't find a config file")?;
+            let mut app = App::new(config::load_config(path)?);
+            for line in app.query(query, chrono::Utc::now()).map_err(|e| eyre::eyre!(e))? {
+                println!("{}", line);
+            }
+            Ok(())
+        };
+        if let Some(at) = matches.value_of("at") {
+            return query(at);
+        }
+        let input = config::find_config(matches.value_of("input").unwrap())
+            .wrap_err("Can't find a config file")?;
+        let Some(output) = matches.value_of("output").map(PathBuf::from) else {
+            eyre::bail!(
+                "No output file given, the usage is 'mvtime convert <input> <output>', \
+or 'mvtime convert --at <query>' for a time query"
+            );
+        };
+        config::convert(input, &output, matches.is_present("force"))?;
+        println!("Config written to {}", output.display());
+        return Ok(());
+    }
+
+    if let Some(("overlap", matches)) = matches.subcommand() {
+        let tracks: Vec<String> =
+            matches.values_of("tracks").into_iter().flatten().map(String::from).collect();
+        let date: Option<chrono::NaiveDate> =
+            matches.is_present("date").then(|| matches.value_of_t("date")).transpose()?;
+        for name in matches.values_of("config").into_iter().flatten() {
+            let path = config::find_config(name).wrap_err("Can't find a config file")?;
+            let app = App::new(config::load_config(path)?);
+            let lines = app
+                .overlap(&tracks, matches.value_of("range"), date)
+                .map_err(|e| eyre::eyre!(e))?;
+            if lines.is_empty() {
+                println!("No shared windows");
+            }
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(("export-ics", matches)) = matches.subcommand() {
+        let days: u32 = matches.value_of_t("days")?;
+        let path = config::find_config(matches.value_of("config").unwrap())
+            .wrap_err("Can't find a config file")?;
+        let mut app = App::new(config::load_config(path)?);
+        let now = chrono::Utc::now();
+        let text = ics::to_string(&app.schedule(now, days), now);
+        let Some(output) = matches.value_of("output").map(PathBuf::from) else {
+            print!("{}", text);
+            return Ok(());
+        };
+        if output.exists() && !matches.is_present("force") {
+            eyre::bail!("{:?} already exists, use --force to overwrite it", output);
+        }
+        std::fs::write(&output, text).wrap_err_with(|| format!("Failed to write {:?}", output))?;
+        println!("Calendar written to {}", output.display());
+        return Ok(());
+    }
+
+    if let Some(("check", matches)) = matches.subcommand() {
+        let mut errors = 0;
+        for name in matches.values_of("config").into_iter().flatten() {
+            let path = config::find_config(name).wrap_err("Can't find a config file")?;
+            errors += check::run(path, &mut io::stdout().lock())?;
+        }
+        std::process::exit(i32::from(errors > 0));
+    }
+
+    let one_time = !matches.is_present("mode");
+    let profiles = profiles(&matches)?;
 
+    let color_support = ColorSupport::from_name(matches.value_of("color").unwrap_or_default());
     if one_time {
-        app.render(&mut terminal)?;
-        return finalize(terminal, true);
+        let color_support = color_support.unwrap_or_else(|| match io::stdout().is_terminal() {
+            true => ColorSupport::from_env(),
+            false => ColorSupport::Mono,
+        });
+        let color = color_support != ColorSupport::Mono;
+        let mut stdout = io::stdout().lock();
+        let (mut printed, mut broken) = (0, vec![]);
+        for path in &profiles {
+            // broken configs are reported on stderr, the rest are still printed
+            let config = match config::load_config(path.clone()) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("{}: {:#}", path.display(), e);
+                    broken.push(e);
+                    continue;
+                }
+            };
+            if printed > 0 {
+                writeln!(stdout)?;
+            }
+            let mut app = App::new(config);
+            app.set_color_support(color_support);
+            print::print(&mut app, output_width(), color, &mut stdout)?;
+            printed += 1;
+        }
+        match (printed, broken.is_empty()) {
+            (0, false) => return Err(broken.remove(0)),
+            (_, false) => std::process::exit(1),
+            _ => return Ok(()),
+        }
     }
 
-    // start config file change watcher
-    let (_watcher, change_event) = start_watcher(config.clone())?;
+    // Load/Parse config files, starting with the first one that loads
+    let color_support = color_support.unwrap_or_else(ColorSupport::from_env);
+    let mut loaded = None;
+    let mut broken = vec![];
+    for (i, path) in profiles.iter().enumerate() {
+        match config::load_config(path.clone()) {
+            Ok(config) if loaded.is_none() => loaded = Some((i, config)),
+            Ok(_) => {}
+            Err(e) => broken.push((path, e)),
+        }
+    }
+    let Some((mut profile, config)) = loaded else {
+        return Err(broken.remove(0).1);
+    };
+    let mut app = App::new(config);
+    app.set_color_support(color_support);
+    // same as a failed switch with 'Tab', the broken ones stay in the cycle
+    if !broken.is_empty() {
+        let lines: Vec<String> =
+            broken.iter().map(|(path, e)| format!("{}: {:#}", path.display(), e)).collect();
+        app.notify(Notice::error(&eyre::eyre!(lines.join("\n"))));
+    }
+
+    let mut terminal = init()?;
+    terminal.size().map(|rect| app.update_layout(rect))?;
+
+    // start config file change watcher, includes and calendars are watched along with the config itself
+    let mut watched = app.watched_files();
+    let mut watcher = ConfigWatcher::new(&watched)?;
+    let mut theme: Option<String> = None;
+    let mut span: Option<Span> = None;
+    let mut reload = false;
 
     'main: loop {
         // render
@@ -114,7 +431,95 @@ in this case it will be searched for in the './' and '<OS_CONFIGS_LOCATION>/mvti
         while dt > 0 {
             let ts = SystemTime::now();
             match poll(dt as u64)? {
-                Some(Event::Key(key)) if is_exit_key(key) => break 'main,
+                // quit still quits over the help, any other key closes it
+                Some(Event::Key(key)) if app.help_open() => {
+                    if app.keys().action(key) == Some(Action::Quit) {
+                        break 'main;
+                    }
+                    app.toggle_help();
+                    app.render(&mut terminal)?;
+                }
+                Some(Event::Key(key)) if app.prompt_open() => {
+                    match key.code {
+                        KeyCode::Esc => app.close_prompt(),
+                        KeyCode::Enter => app.submit_prompt(chrono::Utc::now()),
+                        KeyCode::Backspace => app.prompt_pop(),
+                        KeyCode::Char(c)
+                            if !key
+                                .modifiers
+                                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                        {
+                            app.prompt_push(c)
+                        }
+                        _ => continue,
+                    }
+                    app.render(&mut terminal)?;
+                }
+                Some(Event::Key(key)) => {
+                    match app.keys().action(key) {
+                        Some(Action::Quit) => break 'main,
+                        // closes the detail pane first, then gets back from the inspected time
+                        Some(Action::Close) if app.detail_open() => app.close_detail(),
+                        Some(Action::Close) if app.inspecting() => app.back_to_now(),
+                        Some(Action::Close) => break 'main,
+                        Some(Action::SelectNext) => app.select_next(),
+                        Some(Action::SelectPrev) => app.select_prev(),
+                        Some(Action::ScrollDown) => app.scroll_down(),
+                        Some(Action::ScrollUp) => app.scroll_up(),
+                        Some(Action::Detail) => app.toggle_detail(),
+                        Some(Action::Seconds) => app.toggle_seconds(),
+                        Some(Action::Names) => app.toggle_names(),
+                        Some(Action::Help) => app.toggle_help(),
+                        Some(Action::Dismiss) => app.dismiss(),
+                        Some(Action::Prompt) => app.open_prompt(),
+                        Some(Action::Overlap) => {
+                            let msg = match app.toggle_overlap() {
+                                Some(0) => "No shared windows".to_string(),
+                                Some(n) => format!("{} shared window(s)", n),
+                                None => "Shared windows hidden".to_string(),
+                            };
+                            app.notify(Notice::info(&msg, chrono::Utc::now()));
+                        }
+                        Some(Action::Theme) => {
+                            let name = app.next_theme().to_string();
+                            let msg = format!("Theme {}", name);
+                            app.notify(Notice::info(&msg, chrono::Utc::now()));
+                            theme = Some(name);
+                        }
+                        Some(action @ (Action::ZoomIn | Action::ZoomOut)) => {
+                            let zoomed = app.zoom(action == Action::ZoomIn);
+                            let msg = format!("Span {}", zoomed.name());
+                            app.notify(Notice::info(&msg, chrono::Utc::now()));
+                            span = Some(zoomed);
+                        }
+                        Some(Action::Config) if profiles.len() > 1 => {
+                            let next = (profile + 1) % profiles.len();
+                            match load_app(
+                                &profiles[next],
+                                terminal.size()?,
+                                theme.as_deref(),
+                                span,
+                                color_support,
+                            ) {
+                                Ok(next_app) => {
+                                    app = next_app;
+                                    profile = next;
+                                    let msg = format!("Switched to {}", profiles[next].display());
+                                    app.notify(Notice::info(&msg, chrono::Utc::now()));
+                                }
+                                // stay on the current config if the next one is invalid
+                                Err(e) => app.notify(Notice::error(&e)),
+                            }
+                        }
+                        // reloaded right away, below the key handling
+                        Some(Action::Reload) => {
+                            reload = true;
+                            break;
+                        }
+                        Some(Action::Config) | None => continue,
+                    }
+                    app.render(&mut terminal)?;
+                }
                 Some(Event::Resize(w, h)) => app.update_layout(Rect::new(0, 0, w, h)),
                 _ => {}
             }
@@ -122,14 +527,30 @@ in this case it will be searched for in the './' and '<OS_CONFIGS_LOCATION>/mvti
         }
 
         // config reloading
-        if should_reload(&change_event) {
-            // stay on the current config if the new one is invalid
-            if let Ok(cfg) = config::load_config(config.clone()) {
-                app = App::new(cfg);
-                terminal.size().map(|rect| app.update_layout(rect))?;
+        if std::mem::take(&mut reload) | watcher.changed() {
+            let size = terminal.size()?;
+            match load_app(&profiles[profile], size, theme.as_deref(), span, color_support) {
+                Ok(mut reloaded) => {
+                    // the help overlay stays open over the reloaded config
+                    if app.help_open() {
+                        reloaded.toggle_help();
+                    }
+                    app = reloaded;
+                    app.set_reloaded(chrono::Utc::now(), None);
+                    app.notify(Notice::info("Config reloaded", chrono::Utc::now()));
+                }
+                // stay on the current config if the new one is invalid
+                Err(e) => {
+                    app.set_reloaded(chrono::Utc::now(), Some(e.to_string()));
+                    app.notify(Notice::error(&e));
+                }
             }
         }
+        if app.watched_files() != watched {
+            watched = app.watched_files();
+            watcher = ConfigWatcher::new(&watched)?;
+        }
     }
 
-    finalize(terminal, false)
+    finalize(terminal)
 }
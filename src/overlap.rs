@@ -0,0 +1,97 @@
+use tui::style::Color;
+
+use crate::{
+    config::{TimeRange, TimeTrack},
+    time_to_range, MINUTES_PER_DAY,
+};
+
+// Windows of the day when every track is within one of its ranges of the color, or within any range
+// if there is no color, as UTC minute spans. A window going past the UTC midnight ends after
+// `MINUTES_PER_DAY`. Ranges are given along with the tracks, as dated tracks have them by the day.
+pub fn windows(tracks: &[(&TimeTrack, &[TimeRange])], color: Option<Color>) -> Vec<(u16, u16)> {
+    let day = MINUTES_PER_DAY as usize;
+    let inside = |(track, ranges): &(&TimeTrack, &[TimeRange]), utc: usize| {
+        let local = (utc as i64 + track.offset().num_minutes()).rem_euclid(day as i64) as u16;
+        time_to_range(local, ranges)
+            .map(|i| &ranges[i])
+            .is_some_and(|range| !range.auto() && color.is_none_or(|color| range.color == color))
+    };
+    let shared: Vec<bool> =
+        (0..day).map(|utc| !tracks.is_empty() && tracks.iter().all(|t| inside(t, utc))).collect();
+
+    // runs of the shared minutes, starting the scan after a minute that isn't one
+    let Some(gap) = shared.iter().position(|s| !s) else {
+        return if tracks.is_empty() { vec![] } else { vec![(0, MINUTES_PER_DAY)] };
+    };
+    let mut windows = vec![];
+    let mut start = None;
+    for utc in gap + 1..=gap + day {
+        match (shared[utc % day], start) {
+            (true, None) => start = Some(utc),
+            (false, Some(s)) => {
+                windows.push(((s % day) as u16, (s % day + utc - s) as u16));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    windows.sort_unstable();
+    windows
+}
+
+// whether the UTC minute of the day is within any of the windows
+#[inline]
+pub fn contains(windows: &[(u16, u16)], utc: u16) -> bool {
+    windows.iter().any(|(start, end)| {
+        (*start..*end).contains(&utc) || (*start..*end).contains(&(utc + MINUTES_PER_DAY))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::TimeRange, fill_gaps, to_minutes};
+
+    fn working_hours(offsets: &[(i16, i16)]) -> Vec<TimeTrack> {
+        let mut tracks = vec![];
+        for offset in offsets {
+            let mut track: TimeTrack =
+                ron::from_str(&format!("(name: \"t\", shortname: \"t\", offset: {:?})", offset))
+                    .unwrap();
+            track.ranges = vec![
+                TimeRange::new((9, 0), (12, 0), Color::White),
+                TimeRange::new((12, 0), (13, 0), Color::Gray),
+                TimeRange::new((13, 0), (18, 0), Color::White),
+            ];
+            fill_gaps(&mut track.ranges, Color::DarkGray);
+            tracks.push(track);
+        }
+        tracks
+    }
+
+    #[test]
+    fn shared_windows() {
+        // London and Moscow working hours overlap from 09:00 to 15:00 UTC
+        let tracks = working_hours(&[(0, 0), (3, 0)]);
+        let refs: Vec<_> = tracks.iter().map(|t| (t, t.ranges.as_slice())).collect();
+        assert_eq!(windows(&refs, None), [(to_minutes((9, 0)), to_minutes((15, 0)))]);
+        assert_eq!(
+            windows(&refs, Some(Color::White)),
+            [
+                (to_minutes((10, 0)), to_minutes((12, 0))),
+                (to_minutes((13, 0)), to_minutes((15, 0)))
+            ]
+        );
+        // windows past the UTC midnight stay whole
+        let tracks = working_hours(&[(-10, 0), (-10, -30)]);
+        let refs: Vec<_> = tracks.iter().map(|t| (t, t.ranges.as_slice())).collect();
+        let (start, end) = (to_minutes((19, 30)), to_minutes((28, 0)));
+        assert_eq!(windows(&refs, None), [(start, end)]);
+        assert!(contains(&windows(&refs, None), to_minutes((3, 0))));
+        assert!(!contains(&windows(&refs, None), to_minutes((4, 0))));
+        // no overlap
+        let tracks = working_hours(&[(0, 0), (12, 0)]);
+        let refs: Vec<_> = tracks.iter().map(|t| (t, t.ranges.as_slice())).collect();
+        assert!(windows(&refs, None).is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use crossterm::{
+    queue,
+    style::{Attribute, Color as CColor, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use eyre::{Result, WrapErr};
+use std::io::Write;
+use tui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    style::{Color, Modifier},
+    Terminal,
+};
+
+use crate::App;
+
+// Render app frame off-screen and write it out as plain lines, ANSI-colored if `color` is set.
+// Unlike live mode, this doesn't touch the terminal state, so the output stays in the scrollback.
+pub fn print<W: Write>(app: &mut App, width: u16, color: bool, out: &mut W) -> Result<()> {
+    // margin(1) lines margin(1)
+    let height = app.track_count() as u16 + 2;
+    let mut terminal = Terminal::new(TestBackend::new(width, height))
+        .wrap_err("Off-screen backend initialization failed")?;
+    terminal.size().map(|rect| app.update_layout(rect))?;
+    let frame = app.render(&mut terminal)?;
+    write_buffer(frame.buffer, color, out).wrap_err("Failed to write to stdout")
+}
+
+// write buffer lines skipping top and bottom margins, trailing blanks are trimmed
+fn write_buffer<W: Write>(buf: &Buffer, color: bool, out: &mut W) -> std::io::Result<()> {
+    let area = buf.area;
+    for y in area.top() + 1..area.bottom().saturating_sub(1) {
+        let row = &buf.content[buf.index_of(area.left(), y)..=buf.index_of(area.right() - 1, y)];
+        let len = row
+            .iter()
+            .rposition(|c| c.symbol != " " || (color && c.bg != Color::Reset))
+            .map_or(0, |i| i + 1);
+
+        let mut style = None;
+        for cell in &row[..len] {
+            if color && style != Some((cell.fg, cell.bg, cell.modifier)) {
+                style = Some((cell.fg, cell.bg, cell.modifier));
+                queue!(out, SetAttribute(Attribute::Reset))?;
+                queue!(out, SetForegroundColor(CColor::from(cell.fg)))?;
+                queue!(out, SetBackgroundColor(CColor::from(cell.bg)))?;
+                write_modifier(cell.modifier, out)?;
+            }
+            out.write_all(cell.symbol.as_bytes())?;
+        }
+        if color && style.is_some() {
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+// blinking is left out on purpose, printed lines stay in the scrollback for good
+fn write_modifier<W: Write>(modifier: Modifier, out: &mut W) -> std::io::Result<()> {
+    const ATTRIBUTES: [(Modifier, Attribute); 6] = [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ];
+    for (m, a) in ATTRIBUTES {
+        if modifier.contains(m) {
+            queue!(out, SetAttribute(a))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::{buffer::Buffer, layout::Rect, style::Color};
+
+    use super::write_buffer;
+
+    fn plain(buf: &Buffer) -> String {
+        let mut out = vec![];
+        write_buffer(buf, false, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn margins_skipped_blanks_trimmed() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 4));
+        buf.get_mut(0, 1).set_symbol("a");
+        buf.get_mut(2, 2).set_symbol("b");
+        buf.get_mut(0, 0).set_symbol("x");
+        buf.get_mut(0, 3).set_symbol("x");
+        assert_eq!(plain(&buf), "a\n  b\n");
+    }
+
+    #[test]
+    fn no_escapes_without_color() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 3));
+        buf.get_mut(0, 1).set_symbol("a").set_fg(Color::Red);
+        assert!(!plain(&buf).contains('\x1b'));
+    }
+
+    #[test]
+    fn colored_line_is_reset() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 3));
+        buf.get_mut(0, 1).set_symbol("a").set_fg(Color::Red);
+        let mut out = vec![];
+        write_buffer(&buf, true, &mut out).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.starts_with('\x1b'));
+        assert!(s.ends_with("\x1b[0m\n"));
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    config::{parse_time, TimeTrack},
+    time_to_range, to_minutes, MINUTES_PER_DAY,
+};
+
+// Instant of a "what time is 14:00 London" question: a time in any of the `parse_time` forms
+// and a track name or shortname, in either order. The time is taken on the track's current local date,
+// a query without a track is in the reference (system) time zone.
+pub fn parse(
+    query: &str,
+    tracks: &[TimeTrack],
+    reference: Duration,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    // "9:30 pm" takes two words
+    let split = (1..=usize::min(2, words.len())).rev().find_map(|n| {
+        let head = parse_time(&words[..n].join(" ")).ok().map(|time| (time, &words[n..]));
+        let tail = || {
+            let at = words.len() - n;
+            parse_time(&words[at..].join(" ")).ok().map(|time| (time, &words[..at]))
+        };
+        head.or_else(tail)
+    });
+    let Some(((hour, minute), name)) = split else {
+        return Err(format!("no time in {:?}, expected e.g. '14:00 London'", query));
+    };
+    if minute > 59 || to_minutes((hour, minute)) > MINUTES_PER_DAY {
+        return Err(format!("invalid time {:02}:{:02}", hour, minute));
+    }
+    let offset = match name.join(" ") {
+        name if name.is_empty() => reference,
+        name => tracks[find_track(&name, tracks)?].offset(),
+    };
+    let midnight = (now + offset).date_naive().and_hms_opt(0, 0, 0).unwrap_or_default();
+    let local = midnight + Duration::minutes(to_minutes((hour, minute)).into());
+    Ok(DateTime::from_naive_utc_and_offset(local - offset, Utc))
+}
+
+// track by name or shortname ignoring case, or the only one with the name containing it
+pub(crate) fn find_track(name: &str, tracks: &[TimeTrack]) -> Result<usize, String> {
+    let name = name.to_lowercase();
+    let exact = tracks
+        .iter()
+        .position(|t| t.name.to_lowercase() == name || t.shortname.to_lowercase() == name);
+    if let Some(i) = exact {
+        return Ok(i);
+    }
+    let matching: Vec<usize> =
+        (0..tracks.len()).filter(|i| tracks[*i].name.to_lowercase().contains(&name)).collect();
+    match matching[..] {
+        [i] => Ok(i),
+        [] => {
+            let names: Vec<&str> = tracks.iter().map(|t| t.name.as_str()).collect();
+            Err(format!("unknown track {:?}, tracks are {:?}", name, names))
+        }
+        _ => {
+            let names: Vec<&str> = matching.iter().map(|i| tracks[*i].name.as_str()).collect();
+            Err(format!("ambiguous track {:?}, might be any of {:?}", name, names))
+        }
+    }
+}
+
+// every track's local day and time and its range at the instant, one line per track
+pub fn table(tracks: &[TimeTrack], at: DateTime<Utc>) -> Vec<String> {
+    let width = tracks.iter().map(|t| t.name.len()).max().unwrap_or_default();
+    tracks
+        .iter()
+        .map(|track| {
+            let local = (at + track.offset()).naive_utc();
+            let minutes = to_minutes(track.local_time(at));
+            let range = time_to_range(minutes, &track.ranges)
+                .map(|i| &track.ranges[i])
+                .filter(|range| !range.auto())
+                .map(|range| {
+                    let (start, end) = (range.start, range.end);
+                    format!("{:02}:{:02}-{:02}:{:02}", start.0, start.1, end.0, end.1)
+                });
+            let line = format!(
+                "{:<width$}  {}  {}",
+                track.name,
+                local.format("%a %H:%M"),
+                range.as_deref().unwrap_or("-"),
+                width = width
+            );
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use tui::style::Color;
+
+    use super::*;
+    use crate::{
+        config::{Config, TimeRange},
+        fill_gaps,
+    };
+
+    fn tracks() -> Vec<TimeTrack> {
+        let mut config: Config = ron::from_str(
+            r#"(tracks: [
+                (name: "London", shortname: "LDN", offset: (0, 0)),
+                (name: "Tokyo", shortname: "TYO", offset: (9, 0)),
+                (name: "Toronto", shortname: "YYZ", offset: (-5, 0)),
+            ])"#,
+        )
+        .unwrap();
+        config.tracks[1].ranges = vec![TimeRange::new((9, 0), (17, 0), Color::Blue)];
+        for track in &mut config.tracks {
+            fill_gaps(&mut track.ranges, Color::DarkGray);
+        }
+        config.tracks
+    }
+
+    #[test]
+    fn queries_parsed() {
+        let tracks = tracks();
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 23, 30, 0).unwrap();
+        let at = |query| parse(query, &tracks, Duration::hours(1), now);
+        let expected = Utc.with_ymd_and_hms(2026, 3, 2, 14, 0, 0).unwrap();
+        assert_eq!(at("14:00 London"), Ok(expected));
+        assert_eq!(at("london 2pm"), Ok(expected));
+        assert_eq!(at("2 pm ldn"), Ok(expected));
+        // 00:30 of March 3rd in the reference zone
+        assert_eq!(at("15:00"), Ok(expected + Duration::days(1)));
+        // it's already March 3rd in Tokyo
+        let tokyo = Utc.with_ymd_and_hms(2026, 3, 3, 0, 0, 0).unwrap();
+        assert_eq!(at("9:00 tok"), Ok(tokyo));
+        assert!(at("9:00 to").unwrap_err().starts_with("ambiguous track"));
+        assert!(at("9:00 Paris").unwrap_err().starts_with("unknown track"));
+        assert!(at("London").unwrap_err().starts_with("no time"));
+        assert!(at("25:00 London").is_err());
+    }
+
+    #[test]
+    fn table_of_local_times() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 2, 1, 0, 0).unwrap();
+        assert_eq!(
+            table(&tracks(), at),
+            ["London   Mon 01:00  -", "Tokyo    Mon 10:00  09:00-17:00", "Toronto  Sun 20:00  -"]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::MINUTES_PER_DAY;
+
+// Dark parts of the day, generated for the tracks with a location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Daylight {
+    Night,
+    Twilight,
+}
+
+// sun altitudes of the sunrise with the refraction and of the civil twilight start, degrees
+const SUNRISE: f64 = -0.833;
+const CIVIL: f64 = -6.;
+
+// julian day of 2000-01-01 12:00 UTC
+const J2000: f64 = 2451545.;
+
+// Sunrise equation, https://en.wikipedia.org/wiki/Sunrise_equation.
+// UTC minutes since the `date` midnight when the sun crosses the altitude going up and down,
+// None in a polar night, the whole day around the noon in a polar day.
+fn crossings(date: NaiveDate, (lat, lon): (f64, f64), altitude: f64) -> Option<(f64, f64)> {
+    // days since 2000-01-01
+    let days = f64::from(date.num_days_from_ce() - 730120);
+    let transit_at = days - lon / 360.;
+    let anomaly = (357.5291 + 0.98560028 * transit_at).rem_euclid(360.).to_radians();
+    let center =
+        1.9148 * anomaly.sin() + 0.02 * (2. * anomaly).sin() + 0.0003 * (3. * anomaly).sin();
+    let ecliptic = (anomaly.to_degrees() + center + 180. + 102.9372).rem_euclid(360.).to_radians();
+    let transit = J2000 + transit_at + 0.0053 * anomaly.sin() - 0.0069 * (2. * ecliptic).sin();
+    let declination = (ecliptic.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+    let lat = lat.to_radians();
+    let cos_hour = (altitude.to_radians().sin() - lat.sin() * declination.sin())
+        / (lat.cos() * declination.cos());
+    let half = match cos_hour {
+        c if c > 1. => return None,
+        c if c < -1. => 0.5,
+        c => c.acos().to_degrees() / 360.,
+    };
+    // julian day of the date midnight
+    let midnight = J2000 + days - 0.5;
+    let minutes = |julian: f64| (julian - midnight) * f64::from(MINUTES_PER_DAY);
+    Some((minutes(transit - half), minutes(transit + half)))
+}
+
+// Night and twilight runs of the local day in minutes since the local midnight, the minutes `taken`
+// by the track's own ranges are left out.
+pub fn ranges<F: Fn(u16) -> bool>(
+    location: (f64, f64),
+    date: NaiveDate,
+    offset: i64,
+    taken: F,
+) -> Vec<(u16, u16, Daylight)> {
+    // sun events of the neighbour days might fall on the local day as well
+    let days = [date.pred_opt(), Some(date), date.succ_opt()];
+    let spans = |altitude: f64| -> Vec<(f64, f64)> {
+        days.iter()
+            .zip([-1., 0., 1.])
+            .filter_map(|(day, shift)| {
+                let (up, down) = crossings((*day)?, location, altitude)?;
+                let shift = shift * f64::from(MINUTES_PER_DAY) + offset as f64;
+                Some((up + shift, down + shift))
+            })
+            .collect()
+    };
+    let (light, civil) = (spans(SUNRISE), spans(CIVIL));
+    let within =
+        |spans: &[(f64, f64)], minute: f64| spans.iter().any(|(a, b)| (*a..*b).contains(&minute));
+
+    let mut runs: Vec<(u16, u16, Daylight)> = vec![];
+    for minute in 0..MINUTES_PER_DAY {
+        let middle = f64::from(minute) + 0.5;
+        let kind = match (within(&light, middle), within(&civil, middle)) {
+            _ if taken(minute) => None,
+            (true, _) => None,
+            (false, true) => Some(Daylight::Twilight),
+            (false, false) => Some(Daylight::Night),
+        };
+        match (runs.last_mut(), kind) {
+            (Some((_, end, last)), Some(kind)) if *end == minute && *last == kind => *end += 1,
+            (_, Some(kind)) => runs.push((minute, minute + 1, kind)),
+            _ => {}
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // minutes to "HH:MM"
+    fn hm(minutes: f64) -> String {
+        let minutes = minutes.round() as i64;
+        format!("{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+
+    #[test]
+    fn london_sun_times() {
+        // 2026-06-21 sunrise 04:43, sunset 21:21 BST, i.e. 03:43 and 20:21 UTC
+        let date = NaiveDate::from_ymd_opt(2026, 6, 21).unwrap();
+        let (up, down) = crossings(date, (51.5074, -0.1278), SUNRISE).unwrap();
+        assert!((up - (3. * 60. + 43.)).abs() <= 2., "{}", hm(up));
+        assert!((down - (20. * 60. + 21.)).abs() <= 2., "{}", hm(down));
+        // no sunset in Tromsø in June, no sunrise in December
+        let (up, down) = crossings(date, (69.65, 18.96), SUNRISE).unwrap();
+        assert!((down - up - 1440.).abs() < 1e-6);
+        let december = NaiveDate::from_ymd_opt(2026, 12, 21).unwrap();
+        assert_eq!(crossings(december, (69.65, 18.96), SUNRISE), None);
+    }
+
+    #[test]
+    fn dark_runs_around_own_ranges() {
+        // London in winter, UTC+0: dawn ~07:25, sunrise ~08:05, sunset ~15:55, dusk ~16:35
+        let date = NaiveDate::from_ymd_opt(2026, 12, 21).unwrap();
+        let runs = ranges((51.5074, -0.1278), date, 0, |m| (17 * 60..18 * 60).contains(&m));
+        let kinds: Vec<Daylight> = runs.iter().map(|r| r.2).collect();
+        let (night, twilight) = (Daylight::Night, Daylight::Twilight);
+        assert_eq!(kinds, [night, twilight, twilight, night, night]);
+        assert_eq!(runs[0].0, 0);
+        assert_eq!(runs[3].1, 17 * 60);
+        assert_eq!(runs[4], (18 * 60, MINUTES_PER_DAY, night));
+        assert!((runs[1].1 as i32 - (8 * 60 + 4)).abs() <= 3, "{}", hm(runs[1].1.into()));
+    }
+}
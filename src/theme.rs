@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use tui::{style::Color, symbols};
+
+use crate::{
+    config::{deserialize_color, deserialize_some, deserialize_some_color},
+    solar::Daylight,
+};
+
+// Bar cell glyph of a range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BarGlyph {
+    Thin,
+    Thick,
+    Double,
+    Dotted,
+    Block,
+    LightShade,
+    MediumShade,
+    DarkShade,
+}
+
+impl BarGlyph {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BarGlyph::Thin => symbols::line::NORMAL.horizontal,
+            BarGlyph::Thick => symbols::line::THICK.horizontal,
+            BarGlyph::Double => symbols::line::DOUBLE.horizontal,
+            BarGlyph::Dotted => "┄",
+            BarGlyph::Block => symbols::block::FULL,
+            BarGlyph::LightShade => "░",
+            BarGlyph::MediumShade => "▒",
+            BarGlyph::DarkShade => "▓",
+        }
+    }
+}
+
+// Line bars are thin lines with thick notches at the ends, block bars fill the whole line height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum BarStyle {
+    #[default]
+    Line,
+    Block,
+}
+
+impl BarStyle {
+    // glyph of the ranges without one of their own
+    pub fn glyph(self) -> BarGlyph {
+        match self {
+            BarStyle::Line => BarGlyph::Thin,
+            BarStyle::Block => BarGlyph::Block,
+        }
+    }
+
+    // Ranges can't be told apart by color in monochrome, so each one gets a glyph of its own,
+    // day parts not covered by ranges keep the plain one.
+    pub fn mono_glyphs(self) -> (BarGlyph, &'static [BarGlyph]) {
+        match self {
+            BarStyle::Line => {
+                (BarGlyph::Thin, &[BarGlyph::Thick, BarGlyph::Double, BarGlyph::Dotted])
+            }
+            BarStyle::Block => (
+                BarGlyph::LightShade,
+                &[BarGlyph::Block, BarGlyph::DarkShade, BarGlyph::MediumShade],
+            ),
+        }
+    }
+}
+
+// Colors of everything on the screen except the ranges, which have their own colors, and the bar style.
+// Fields missing in a config theme are taken from the "dark" preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Theme {
+    // screen background, also the blank screen color when the terminal is too small
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background: Color,
+    // color of the day parts not covered by ranges
+    #[serde(deserialize_with = "deserialize_color")]
+    pub base: Color,
+    // clock digits over the filled background
+    #[serde(deserialize_with = "deserialize_color")]
+    pub fill_fg: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub clock: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub title: Color,
+    // clock ':' separators, same as the digits if not set
+    #[serde(deserialize_with = "deserialize_some_color", skip_serializing_if = "Option::is_none")]
+    pub separator: Option<Color>,
+    // bar end notches, the color of the first and the last range if not set
+    #[serde(deserialize_with = "deserialize_some_color", skip_serializing_if = "Option::is_none")]
+    pub notch: Option<Color>,
+    // night and twilight of the tracks with a location
+    #[serde(deserialize_with = "deserialize_color")]
+    pub night: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub twilight: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub info: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub error: Color,
+    pub bar: BarStyle,
+    // glyph of the ranges without one of their own, the bar style one if not set
+    #[serde(deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<BarGlyph>,
+}
+
+impl Theme {
+    #[inline]
+    pub fn glyph(&self) -> BarGlyph {
+        self.glyph.unwrap_or_else(|| self.bar.glyph())
+    }
+
+    pub fn daylight(&self, kind: Daylight) -> Color {
+        match kind {
+            Daylight::Night => self.night,
+            Daylight::Twilight => self.twilight,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        DARK
+    }
+}
+
+const DARK: Theme = Theme {
+    background: Color::Reset,
+    base: Color::DarkGray,
+    fill_fg: Color::Black,
+    clock: Color::Reset,
+    title: Color::Reset,
+    separator: None,
+    notch: None,
+    night: Color::Rgb(25, 35, 70),
+    twilight: Color::Rgb(80, 70, 120),
+    info: Color::Green,
+    error: Color::Red,
+    bar: BarStyle::Line,
+    glyph: None,
+};
+
+const LIGHT: Theme = Theme {
+    background: Color::White,
+    base: Color::Gray,
+    fill_fg: Color::White,
+    clock: Color::Black,
+    title: Color::Black,
+    separator: None,
+    notch: None,
+    night: Color::Rgb(90, 110, 160),
+    twilight: Color::Rgb(170, 160, 210),
+    info: Color::Green,
+    error: Color::Red,
+    bar: BarStyle::Line,
+    glyph: None,
+};
+
+const SOLARIZED: Theme = Theme {
+    background: Color::Rgb(0, 43, 54),
+    base: Color::Rgb(88, 110, 117),
+    fill_fg: Color::Rgb(0, 43, 54),
+    clock: Color::Rgb(147, 161, 161),
+    title: Color::Rgb(131, 148, 150),
+    separator: Some(Color::Rgb(181, 137, 0)),
+    notch: None,
+    night: Color::Rgb(7, 54, 66),
+    twilight: Color::Rgb(108, 113, 196),
+    info: Color::Rgb(133, 153, 0),
+    error: Color::Rgb(220, 50, 47),
+    bar: BarStyle::Line,
+    glyph: None,
+};
+
+const HIGH_CONTRAST: Theme = Theme {
+    background: Color::Black,
+    base: Color::Gray,
+    fill_fg: Color::Black,
+    clock: Color::White,
+    title: Color::White,
+    separator: None,
+    notch: Some(Color::White),
+    night: Color::Blue,
+    twilight: Color::Magenta,
+    info: Color::LightGreen,
+    error: Color::LightRed,
+    bar: BarStyle::Block,
+    glyph: None,
+};
+
+const MONOCHROME: Theme = Theme {
+    background: Color::Reset,
+    base: Color::DarkGray,
+    fill_fg: Color::Black,
+    clock: Color::White,
+    title: Color::White,
+    separator: None,
+    notch: Some(Color::Gray),
+    night: Color::Black,
+    twilight: Color::Gray,
+    info: Color::White,
+    error: Color::White,
+    bar: BarStyle::Line,
+    glyph: None,
+};
+
+// built-in themes in the order they are cycled through, "dark" is the default one
+pub const PRESETS: [(&str, Theme); 5] = [
+    ("dark", DARK),
+    ("light", LIGHT),
+    ("solarized", SOLARIZED),
+    ("high-contrast", HIGH_CONTRAST),
+    ("monochrome", MONOCHROME),
+];
+
+pub const DEFAULT_THEME: &str = PRESETS[0].0;
+
+pub fn preset(name: &str) -> Option<Theme> {
+    PRESETS.iter().find(|(preset, _)| *preset == name).map(|(_, theme)| *theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::style::Color;
+
+    use super::*;
+
+    #[test]
+    fn missing_fields_from_dark() {
+        let theme: Theme = ron::from_str("(background: White, notch: Red)").unwrap();
+        assert_eq!(theme.background, Color::White);
+        assert_eq!(theme.notch, Some(Color::Red));
+        assert_eq!(theme.base, preset("dark").unwrap().base);
+        assert_eq!(theme.bar, BarStyle::Line);
+        assert_eq!(theme.glyph(), BarGlyph::Thin);
+        assert_eq!(preset("nope"), None);
+        let theme: Theme = ron::from_str("(bar: Block, glyph: Double)").unwrap();
+        assert_eq!(theme.glyph(), BarGlyph::Double);
+        assert_eq!(preset("high-contrast").unwrap().glyph(), BarGlyph::Block);
+    }
+}
@@ -1,20 +1,29 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use tui::{
     backend::Backend,
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
     symbols,
-    widgets::{Block, Widget},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
     Frame,
 };
 
 use crate::{
-    config::{Colors, TimeTrack},
-    point_to_time, time_to_range,
+    color::ColorSupport,
+    config::{fmt_offset, TimeRange, TimeTrack},
+    keys::{Action, KeyMap},
+    next_transition, overlap, point_to_span_time,
+    theme::{BarGlyph, BarStyle, Theme},
+    time_to_range, to_hour_minute, to_minutes, MINUTES_PER_DAY,
 };
 
 const DEBUG_LAYOUT: bool = false;
 
+// bar cell of a midnight in multi-day spans
+const DAY_SEPARATOR: &str = symbols::line::VERTICAL;
+
 #[inline(always)]
 pub fn fill<B: Backend>(f: &mut Frame<B>, area: Rect, color: Color) {
     f.render_widget(Block::default().style(Style::default().bg(color)), area);
@@ -39,6 +48,65 @@ pub struct LineAux {
     pub current_range: usize,
     pub title_text: String,
     pub local_time: (u16, u16),
+    // minutes since the local midnight, seconds included
+    pub now: f64,
+    // part of the current range passed, 0..1
+    pub progress: f64,
+    // start of the bars span in minutes since the local midnight and its length, see `Span::window`
+    pub window: (i32, u16),
+    // ranges of every day of a multi-day span of a dated track, the first day is `days_from`
+    pub days: Vec<Vec<TimeRange>>,
+    pub days_from: Option<NaiveDate>,
+}
+
+impl LineAux {
+    // Day of the minute since the span start relative to the local today, the ranges of the day,
+    // and the minute of the day
+    pub fn day_at<'a>(&'a self, track: &'a TimeTrack, minute: f64) -> (i32, &'a [TimeRange], f64) {
+        let day = f64::from(MINUTES_PER_DAY);
+        let start = f64::from(self.window.0);
+        let n = ((start + minute) / day).floor();
+        let first = (start / day).floor();
+        let ranges = self.days.get((n - first) as usize).map_or(&track.ranges[..], |r| &r[..]);
+        (n as i32, ranges, start + minute - n * day)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoticeKind {
+    Info,
+    Error,
+}
+
+// Message panel shown at the bottom of the screen over the tracks,
+// errors stay until dismissed, info notices go away by themselves.
+#[derive(Debug, Clone)]
+pub struct Notice {
+    pub kind: NoticeKind,
+    pub text: String,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Notice {
+    pub fn info(text: &str, now: DateTime<Utc>) -> Self {
+        const TTL: i64 = 3;
+        Self {
+            kind: NoticeKind::Info,
+            text: text.to_string(),
+            until: Some(now + Duration::seconds(TTL)),
+        }
+    }
+
+    // one line per error in the chain, outermost first
+    pub fn error(err: &eyre::Report) -> Self {
+        let text = err.chain().map(|e| e.to_string()).collect::<Vec<_>>().join("\n");
+        Self { kind: NoticeKind::Error, text, until: None }
+    }
+
+    #[inline]
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_some_and(|until| now >= until)
+    }
 }
 
 // fill one line with symbol
@@ -100,11 +168,12 @@ impl<F: Fn(&mut Buffer)> Widget for Apply<F> {
 }
 
 #[inline]
+#[allow(clippy::unnecessary_cast)]
 pub fn render_badge<B: Backend>(
     frame: &mut Frame<B>,
     line: &LineAux,
     track: &TimeTrack,
-    colors: &Colors,
+    theme: &Theme,
 ) {
     debug_fill(frame, line.badge, Color::Magenta);
     const THIN: &str = symbols::block::NINE_LEVELS.one_eighth;
@@ -112,73 +181,121 @@ pub fn render_badge<B: Backend>(
     //const SYMBOL: &str = symbols::DOT;
 
     let range_color = track.ranges[line.current_range as usize].color;
-    let symbol = if range_color == colors.base { THIN } else { THICK };
+    let symbol = if range_color == theme.base { THIN } else { THICK };
 
     frame.render_widget(Glyph::new(symbol, Style::default().fg(range_color)), line.badge);
 }
 
 #[inline]
-pub fn render_title<B: Backend>(frame: &mut Frame<B>, line: &LineAux, colors: &Colors) {
+pub fn render_title<B: Backend>(
+    frame: &mut Frame<B>,
+    line: &LineAux,
+    theme: &Theme,
+    selected: bool,
+) {
     debug_fill(frame, line.title, Color::Cyan);
-    frame.render_widget(
-        TextLine::new(
-            &line.title_text,
-            Style::default().fg(colors.title).add_modifier(Modifier::BOLD),
-        ),
-        line.title,
-    );
+    let mut style = Style::default().fg(theme.title).add_modifier(Modifier::BOLD);
+    if selected {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    frame.render_widget(TextLine::new(&line.title_text, style), line.title);
 }
 
 #[inline]
+#[allow(clippy::unnecessary_cast)]
 pub fn render_clock<B: Backend>(
     frame: &mut Frame<B>,
     seconds: u16,
     line: &LineAux,
     track: &TimeTrack,
-    colors: &Colors,
+    theme: &Theme,
+    mono: bool,
 ) {
     debug_fill(frame, line.clock, Color::Blue);
     let label = &track.time_label;
     let (hour, minute) = line.local_time;
-    let range = track.ranges[line.current_range as usize];
+    let range = &track.ranges[line.current_range as usize];
     let rc = range.color;
-    let text = if label.seconds {
+    let mut text = if label.seconds {
         format!(" {:02}:{:02}:{:02} ", hour, minute, seconds)
     } else {
         format!(" {:02}:{:02} ", hour, minute)
     };
+    if label.progress {
+        let percent = (line.progress * 100.).floor().clamp(0., 99.);
+        text.push_str(&format!("{:>2}% ", percent));
+    }
     let fill = range.fill.unwrap_or(label.fill);
     let blink = range.blink.unwrap_or(label.blink);
     let use_range_color = range.use_range_color.unwrap_or(label.use_range_color);
 
     let (fg, bg) = match (use_range_color, fill) {
-        (true, true) => (colors.fill_fg, rc),
-        (true, false) => (rc, Color::Reset),
-        (false, true) => (colors.fill_fg, colors.base),
-        (false, false) => (colors.clock, Color::Reset),
+        (true, true) => (theme.fill_fg, rc),
+        (true, false) => (rc, theme.background),
+        (false, true) => (theme.fill_fg, theme.base),
+        (false, false) => (theme.clock, theme.background),
     };
-    let style = Style::default().add_modifier(Modifier::BOLD).fg(fg).bg(bg);
+    let mut style = Style::default().add_modifier(Modifier::BOLD).fg(fg).bg(bg);
+    // there is no background to fill in monochrome
+    if mono && fill {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
     frame.render_widget(TextLine::new(text.as_str(), style), line.clock);
 
+    // ':' separators
+    let mut style = style.fg(theme.separator.unwrap_or(fg));
     if blink {
-        let style = style.add_modifier(Modifier::SLOW_BLINK);
-        frame.render_widget(
-            Apply(|buf| {
-                buf.get_mut(line.clock.x + 3, line.clock.y).set_style(style);
-                if label.seconds {
-                    buf.get_mut(line.clock.x + 6, line.clock.y).set_style(style);
-                }
-            }),
-            line.clock,
-        );
+        style = style.add_modifier(Modifier::SLOW_BLINK);
     }
+    frame.render_widget(
+        Apply(|buf| {
+            buf.get_mut(line.clock.x + 3, line.clock.y).set_style(style);
+            if label.seconds {
+                buf.get_mut(line.clock.x + 6, line.clock.y).set_style(style);
+            }
+        }),
+        line.clock,
+    );
+}
+
+// Range glyph, if it has one, otherwise the theme bar style one.
+// In monochrome the ranges without glyphs take distinct glyphs in turn.
+fn range_symbols(ranges: &[TimeRange], theme: &Theme, mono: bool) -> Vec<&'static str> {
+    let (gap, cycle) = theme.bar.mono_glyphs();
+    let mut n = 0;
+    let mut next = || {
+        n += 1;
+        cycle[(n - 1) % cycle.len()]
+    };
+    ranges
+        .iter()
+        .map(|range| match range.glyph {
+            Some(glyph) => glyph,
+            None if mono && range.gap => gap,
+            None if mono => next(),
+            None => theme.glyph(),
+        })
+        .map(BarGlyph::symbol)
+        .collect()
 }
 
 #[inline]
-pub fn render_bars<B: Backend>(frame: &mut Frame<B>, line: &LineAux, track: &TimeTrack) {
+#[allow(clippy::useless_conversion)]
+pub fn render_bars<B: Backend>(
+    frame: &mut Frame<B>,
+    line: &LineAux,
+    track: &TimeTrack,
+    theme: &Theme,
+    mono: bool,
+) {
     debug_fill(frame, line.chart, Color::Blue);
-    const SYMBOL: &str = symbols::line::NORMAL.horizontal;
     const NOTCH: &str = symbols::line::THICK.horizontal;
+    let span = line.window.1;
+    // color of the range at the minute since the span start
+    let color_at = |minute: u16| {
+        let (_, ranges, minute) = line.day_at(track, f64::from(minute));
+        time_to_range(minute as u16, ranges).map_or(theme.background, |i| ranges[i].color)
+    };
     frame.render_widget(
         Apply(|buf| {
             let (lbar, rbar) = &line.bars;
@@ -189,18 +306,354 @@ pub fn render_bars<B: Backend>(frame: &mut Frame<B>, line: &LineAux, track: &Tim
             let rn = buf.index_of(rbar.right(), y);
             let cells = (l0..ln).into_iter().chain((r0..rn).into_iter());
             let total_width = lbar.width + rbar.width;
+            // day of the previous cell and the range symbols of the day
+            let mut day: Option<(i32, Vec<&str>)> = None;
             for (i, ci) in cells.enumerate() {
-                let time = point_to_time(i as u16, total_width);
-                let range_idx = time_to_range(time, &track.ranges).unwrap();
-                let range = track.ranges[range_idx];
+                let time = point_to_span_time(i as u16, total_width, span);
+                let (n, ranges, minute) = line.day_at(track, f64::from(time));
+                let range_idx = time_to_range(minute as u16, ranges).unwrap();
+                let range = &ranges[range_idx];
                 let style = Style::default().fg(range.color);
-                buf.content[ci].set_style(style).set_symbol(SYMBOL);
+                let first = day.is_none();
+                if day.as_ref().is_none_or(|(last, _)| *last != n) {
+                    day = Some((n, range_symbols(ranges, theme, mono)));
+                    if !first {
+                        buf.content[ci].set_style(style.fg(theme.title)).set_symbol(DAY_SEPARATOR);
+                        continue;
+                    }
+                }
+                let symbols = day.as_ref().map_or(&[][..], |(_, symbols)| &symbols[..]);
+                buf.content[ci].set_style(style).set_symbol(symbols[range_idx]);
+            }
+            // block bars have no room for notches
+            if theme.bar == BarStyle::Line {
+                let (first, last) = (color_at(0), color_at(span - 1));
+                buf.content[l0].set_symbol(NOTCH).set_fg(theme.notch.unwrap_or(first));
+                buf.content[rn - 1].set_symbol(NOTCH).set_fg(theme.notch.unwrap_or(last));
             }
-            buf.content[l0].set_symbol(NOTCH).set_fg(track.ranges[0].color);
-            buf.content[rn - 1]
-                .set_symbol(NOTCH)
-                .set_fg(track.ranges[track.ranges.len() - 1].color);
         }),
         frame.size(),
     );
 }
+
+// Bars with the range borders and the day ends at 1/8 of a cell precision.
+// Cells are laid out from the current time outwards, a cell with a border in it gets a left eighths block
+// of the left side color over the right side color, cells wholly within a range are drawn with its glyph.
+#[inline]
+pub fn render_hires_bars<B: Backend>(
+    frame: &mut Frame<B>,
+    line: &LineAux,
+    track: &TimeTrack,
+    theme: &Theme,
+) {
+    debug_fill(frame, line.chart, Color::Blue);
+    const EIGHTHS: [&str; 9] = [
+        " ",
+        symbols::block::ONE_EIGHTH,
+        symbols::block::ONE_QUARTER,
+        symbols::block::THREE_EIGHTHS,
+        symbols::block::HALF,
+        symbols::block::FIVE_EIGHTHS,
+        symbols::block::THREE_QUARTERS,
+        symbols::block::SEVEN_EIGHTHS,
+        symbols::block::FULL,
+    ];
+    let day = f64::from(MINUTES_PER_DAY);
+    let span = f64::from(line.window.1);
+    let start = f64::from(line.window.0);
+    let now = line.now - start;
+    let per_cell = span / f64::from(line.clock.left() - line.chart.left());
+    // day and range at the minute since the span start, None outside of the span
+    let range_at = |minute: f64| match minute {
+        m if (0. ..span).contains(&m) => {
+            let (n, ranges, m) = line.day_at(track, m);
+            time_to_range(m as u16, ranges).map(|i| (n, i, &ranges[i]))
+        }
+        _ => None,
+    };
+    let color =
+        |range: Option<(i32, usize, &TimeRange)>| range.map_or(theme.background, |r| r.2.color);
+
+    frame.render_widget(
+        Apply(|buf| {
+            let (lbar, rbar) = &line.bars;
+            let left = (0..lbar.width).map(|i| {
+                let j = f64::from(lbar.width - i);
+                (lbar.left() + i, now - j * per_cell)
+            });
+            let right = (0..rbar.width).map(|i| (rbar.left() + i, now + f64::from(i) * per_cell));
+            for (x, cell_start) in left.chain(right) {
+                let end = cell_start + per_cell;
+                let (first, last) = (range_at(cell_start), range_at(end - 1e-6));
+                let cell = buf.get_mut(x, lbar.y);
+                // a midnight within the span
+                let midnight = ((cell_start + start) / day).ceil() * day - start;
+                if cell_start > 0. && midnight < end && midnight < span {
+                    cell.set_symbol(DAY_SEPARATOR).set_fg(theme.title).set_bg(theme.background);
+                    continue;
+                }
+                let index = |range: Option<(i32, usize, &TimeRange)>| range.map(|r| (r.0, r.1));
+                if index(first) == index(last) {
+                    let symbol = first
+                        .and_then(|r| r.2.glyph)
+                        .map_or(symbols::block::FULL, BarGlyph::symbol);
+                    cell.set_symbol(symbol).set_fg(color(first));
+                    continue;
+                }
+                // where the left side ends within the cell
+                let border = match first {
+                    Some((n, _, range)) => {
+                        f64::from(n) * day + f64::from(to_minutes(range.end)) - start
+                    }
+                    None => 0.,
+                };
+                let level =
+                    (((border - cell_start) / per_cell) * 8.).round().clamp(0., 8.) as usize;
+                if first.is_none() {
+                    // the day starts within the cell, there is no right eighths block to draw
+                    // the background on the left, so the colors are swapped instead
+                    cell.set_symbol(EIGHTHS[level])
+                        .set_fg(color(last))
+                        .set_bg(theme.background)
+                        .modifier
+                        .insert(Modifier::REVERSED);
+                } else {
+                    cell.set_symbol(EIGHTHS[level]).set_fg(color(first)).set_bg(color(last));
+                }
+            }
+        }),
+        frame.size(),
+    );
+}
+
+// Key bindings, config file, reload status and version, closes on any key
+pub fn render_help<B: Backend>(
+    frame: &mut Frame<B>,
+    keys: &KeyMap,
+    config: &str,
+    status: &str,
+    theme: &Theme,
+) {
+    // unbound actions are left out
+    let bindings: Vec<(String, &str)> = Action::ALL
+        .iter()
+        .map(|action| {
+            let chords: Vec<String> = keys.keys(*action).iter().map(|c| c.to_string()).collect();
+            (chords.join(", "), action.description())
+        })
+        .filter(|(chords, _)| !chords.is_empty())
+        .collect();
+    let width = bindings.iter().map(|(chords, _)| chords.len() + 2).max().unwrap_or_default();
+    let width = usize::max(width, 9);
+    let label = |text: &str| {
+        Span::styled(format!("{:<width$}", text, width = width), Style::default().fg(theme.base))
+    };
+    let mut lines: Vec<Spans> = bindings
+        .iter()
+        .map(|(chords, action)| Spans::from(vec![label(chords), Span::raw(*action)]))
+        .collect();
+    lines.push(Spans::default());
+    lines.push(Spans::from(vec![label("Config"), Span::raw(config)]));
+    lines.push(Spans::from(vec![label("Reload"), Span::raw(status)]));
+    lines.push(Spans::from(vec![label("Version"), Span::raw(env!("CARGO_PKG_VERSION"))]));
+
+    const TITLE: &str = " Help, any key to close ";
+    // longest line + borders
+    let screen = frame.size();
+    let width = lines.iter().map(Spans::width).chain([TITLE.len()]).max().unwrap_or_default();
+    let width = u16::min(width as u16 + 2, screen.width);
+    let height = u16::min(lines.len() as u16 + 2, screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title))
+        .title(TITLE);
+    let text = Paragraph::new(lines)
+        .style(Style::default().fg(theme.title).bg(theme.background))
+        .block(block);
+    frame.render_widget(Clear, area);
+    frame.render_widget(text, area);
+}
+
+#[inline]
+fn hh_mm(minutes: i64) -> String {
+    let (hour, minute) = to_hour_minute(minutes.rem_euclid(MINUTES_PER_DAY.into()) as u16);
+    format!("{:02}:{:02}", hour, minute)
+}
+
+// Selected track details over the tracks: offset, local date and time, the next range border
+// and the ranges in both the track and the reference time zones.
+pub fn render_detail<B: Backend>(
+    frame: &mut Frame<B>,
+    track: &TimeTrack,
+    now: DateTime<Utc>,
+    reference: Duration,
+    theme: &Theme,
+) {
+    let label = |text: &str| Span::styled(format!("{:<11}", text), Style::default().fg(theme.base));
+    let local = (now + track.offset()).naive_utc();
+    let minutes = to_minutes(track.local_time(now));
+    let next = match next_transition(minutes, &track.ranges) {
+        Some(left) => {
+            format!("{}, in {}h {:02}m", hh_mm(i64::from(minutes + left)), left / 60, left % 60)
+        }
+        None => "-".to_string(),
+    };
+    let reference_minutes = reference.num_minutes();
+    let reference_offset = ((reference_minutes / 60) as i16, (reference_minutes % 60) as i16);
+    // ranges start at the same instant in both zones, only the clock reading differs
+    let shift = reference_minutes - track.offset().num_minutes();
+
+    let mut lines = vec![
+        Spans::from(vec![label("Offset"), Span::raw(fmt_offset(track.offset))]),
+        Spans::from(vec![label("Local"), Span::raw(local.format("%a %Y-%m-%d %H:%M").to_string())]),
+        Spans::from(vec![label("Next"), Span::raw(next)]),
+        Spans::default(),
+        Spans::from(vec![
+            label("Ranges"),
+            Span::raw(format!("{:<14}{}", "local", fmt_offset(reference_offset))),
+        ]),
+    ];
+    let ranges = track.ranges.iter().filter(|r| !r.auto());
+    for range in ranges.clone() {
+        let (start, end) = (i64::from(to_minutes(range.start)), i64::from(to_minutes(range.end)));
+        lines.push(Spans::from(vec![
+            Span::raw("  "),
+            Span::styled(symbols::block::FULL.repeat(2), Style::default().fg(range.color)),
+            Span::raw(" ".repeat(7)),
+            Span::raw(format!("{}-{}   ", hh_mm(start), hh_mm(end))),
+            Span::raw(format!("{}-{}", hh_mm(start + shift), hh_mm(end + shift))),
+        ]));
+    }
+    if ranges.count() == 0 {
+        lines.push(Spans::from(vec![label(""), Span::raw("-")]));
+    }
+
+    // text lines + borders, centered
+    let screen = frame.size();
+    let width = u16::min(42, screen.width);
+    let height = u16::min(lines.len() as u16 + 2, screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title))
+        .title(format!(" {} ", track.name));
+    let text = Paragraph::new(lines)
+        .style(Style::default().fg(theme.title).bg(theme.background))
+        .block(block);
+    frame.render_widget(Clear, area);
+    frame.render_widget(text, area);
+}
+
+// one line at the bottom margin, the time query with its error or the inspected instant
+pub fn render_prompt<B: Backend>(
+    frame: &mut Frame<B>,
+    text: &str,
+    error: Option<&str>,
+    theme: &Theme,
+) {
+    let screen = frame.size();
+    let area = Rect::new(screen.x, screen.bottom() - 1, screen.width, 1);
+    let mut spans = vec![Span::styled(text.to_string(), Style::default().fg(theme.title))];
+    if let Some(error) = error {
+        spans.push(Span::styled(format!("  {}", error), Style::default().fg(theme.error)));
+    }
+    let line = Paragraph::new(Spans::from(spans)).style(Style::default().bg(theme.background));
+    frame.render_widget(Clear, area);
+    frame.render_widget(line, area);
+}
+
+// Shared windows over the bars in reverse video, cells are mapped to the times the same way
+// `render_bars` and `render_hires_bars` do.
+pub fn render_band<B: Backend>(
+    frame: &mut Frame<B>,
+    line: &LineAux,
+    track: &TimeTrack,
+    windows: &[(u16, u16)],
+    hires: bool,
+) {
+    let day = f64::from(MINUTES_PER_DAY);
+    let span = f64::from(line.window.1);
+    let start = f64::from(line.window.0);
+    let now = line.now - start;
+    let offset = track.offset().num_minutes() as f64;
+    let (lbar, rbar) = &line.bars;
+    let total_width = lbar.width + rbar.width;
+    let per_cell = span / f64::from(line.clock.left() - line.chart.left());
+    // minute of the cell middle since the span start
+    let minute = |i: u16| {
+        if !hires {
+            f64::from(point_to_span_time(i, total_width, line.window.1))
+        } else if i < lbar.width {
+            now - (f64::from(lbar.width - i) - 0.5) * per_cell
+        } else {
+            now + (f64::from(i - lbar.width) + 0.5) * per_cell
+        }
+    };
+    frame.render_widget(
+        Apply(|buf| {
+            let cells = (lbar.left()..lbar.right()).chain(rbar.left()..rbar.right());
+            for (i, x) in cells.enumerate() {
+                let minute = minute(i as u16);
+                if !(0. ..span).contains(&minute) {
+                    continue;
+                }
+                let utc = (minute + start - offset).rem_euclid(day) as u16;
+                if overlap::contains(windows, utc) {
+                    buf.get_mut(x, lbar.y).modifier.insert(Modifier::REVERSED);
+                }
+            }
+        }),
+        frame.size(),
+    );
+}
+
+// map every cell color to the nearest one the terminal supports
+#[inline]
+pub fn downsample<B: Backend>(frame: &mut Frame<B>, support: ColorSupport) {
+    if support == ColorSupport::TrueColor {
+        return;
+    }
+    frame.render_widget(
+        Apply(|buf| {
+            for cell in &mut buf.content {
+                cell.fg = support.map(cell.fg);
+                cell.bg = support.map(cell.bg);
+            }
+        }),
+        frame.size(),
+    );
+}
+
+#[inline]
+pub fn render_notice<B: Backend>(frame: &mut Frame<B>, notice: &Notice, theme: &Theme) {
+    let (title, color) = match notice.kind {
+        NoticeKind::Info => ("", theme.info),
+        NoticeKind::Error => (" Config error, 'x' to dismiss ", theme.error),
+    };
+    // text lines + top and bottom borders
+    let screen = frame.size();
+    let height = u16::min(notice.text.lines().count() as u16 + 2, screen.height);
+    let area = Rect::new(screen.x, screen.bottom() - height, screen.width, height);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(title);
+    let text = Paragraph::new(notice.text.as_str())
+        .style(Style::default().fg(theme.title).bg(theme.background))
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(Clear, area);
+    frame.render_widget(text, area);
+}
@@ -0,0 +1,124 @@
+use eyre::{Result, WrapErr};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+// quiet period after the last change before the files are considered saved
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// Watches config files for changes.
+// Editors often save via rename-replace, which leaves a plain file watch pointing to the
+// moved-away inode, so the parent directories are watched and events are filtered by path.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    rx: Receiver<(Instant, notify::Result<Event>)>,
+    files: Vec<PathBuf>,
+    last_change: Option<Instant>,
+}
+
+impl ConfigWatcher {
+    pub fn new(files: &[PathBuf]) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = RecommendedWatcher::new(
+            move |e| {
+                // receiver is gone only when the app is shutting down
+                let _ = tx.send((Instant::now(), e));
+            },
+            notify::Config::default(),
+        )
+        .wrap_err("Failed to start config file watcher")?;
+
+        let mut dirs: Vec<&Path> = files.iter().filter_map(|f| f.parent()).collect();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .wrap_err_with(|| format!("Failed to watch {:?} for changes", dir))?;
+        }
+        Ok(Self { _watcher: watcher, rx, files: files.to_vec(), last_change: None })
+    }
+
+    // Drains all the queued events, returns true once after a burst of changes settles down
+    pub fn changed(&mut self) -> bool {
+        for (ts, e) in self.rx.try_iter() {
+            if e.is_ok_and(|e| is_relevant(&e, &self.files)) {
+                self.last_change = Some(ts);
+            }
+        }
+        settled(&mut self.last_change, Instant::now())
+    }
+}
+
+#[inline]
+fn settled(last_change: &mut Option<Instant>, now: Instant) -> bool {
+    match last_change {
+        Some(ts) if now.duration_since(*ts) >= DEBOUNCE => {
+            *last_change = None;
+            true
+        }
+        _ => false,
+    }
+}
+
+// any create/modify/remove event touching one of the watched files
+#[inline]
+fn is_relevant(e: &Event, files: &[PathBuf]) -> bool {
+    matches!(
+        e.kind,
+        EventKind::Any | EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) && e.paths.iter().any(|p| files.contains(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::{
+        event::{AccessKind, CreateKind, ModifyKind, RemoveKind, RenameMode},
+        Event, EventKind,
+    };
+    use std::{
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    use super::{is_relevant, settled, DEBOUNCE};
+
+    fn files() -> Vec<PathBuf> {
+        vec![PathBuf::from("/cfg/default.ron")]
+    }
+
+    #[test]
+    fn rename_replace_save_is_relevant() {
+        let moved = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::To)))
+            .add_path(PathBuf::from("/cfg/default.ron"));
+        let created = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/cfg/default.ron"));
+        let removed = Event::new(EventKind::Remove(RemoveKind::File))
+            .add_path(PathBuf::from("/cfg/default.ron"));
+        assert!(is_relevant(&moved, &files()));
+        assert!(is_relevant(&created, &files()));
+        assert!(is_relevant(&removed, &files()));
+    }
+
+    #[test]
+    fn other_files_and_access_ignored() {
+        let swap = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/cfg/.default.ron.swp"));
+        let read = Event::new(EventKind::Access(AccessKind::Read))
+            .add_path(PathBuf::from("/cfg/default.ron"));
+        assert!(!is_relevant(&swap, &files()));
+        assert!(!is_relevant(&read, &files()));
+    }
+
+    #[test]
+    fn burst_settles_once() {
+        let now = Instant::now();
+        let mut last = Some(now);
+        assert!(!settled(&mut last, now + Duration::from_millis(10)));
+        assert!(settled(&mut last, now + DEBOUNCE));
+        assert!(!settled(&mut last, now + DEBOUNCE * 2));
+    }
+}